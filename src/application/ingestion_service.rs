@@ -1,24 +1,111 @@
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{info, debug, error, warn};
 use chrono::{Utc, DateTime};
-use uuid::Uuid;
+use futures_util::StreamExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use crate::domain::{
     error::IngestionError,
-    models::{FileToProcess, IngestionConfigRule, IngestionLog, IngestionStatus},
-    ports::{FileFetcher, DataParser, ConfigRepository, DataRepository, LogRepository},
+    file_type::{detect_file_type, SNIFF_BYTES},
+    document_schema::DocumentSchema,
+    transform::Transform,
+    models::{DocumentCounts, DuplicatePolicy, FileMetadata, FileToProcess, IngestionConfigRule, IngestionLog, IngestionStatus, SourceVersion},
+    ports::{FileFetcher, FileStream, Decompressor, OpenedFile, DataParser, ConfigRepository, DataRepository, LogRepository, SOURCE_ROW_FIELD},
 };
+use crate::infrastructure::spool::SpoolFile;
 
 pub struct IngestionService {
     file_fetcher: Arc<dyn FileFetcher>,
+    decompressor: Arc<dyn Decompressor>,
     data_parser: Arc<dyn DataParser>,
     config_repo: Arc<dyn ConfigRepository>,
     data_repo: Arc<dyn DataRepository>,
     log_repo: Arc<dyn LogRepository>,
+    stale_log_timeout: Duration,
+}
+
+/// How long a log may stay in progress before it is taken for a crashed ingestion.
+pub const DEFAULT_STALE_LOG_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// A single file to ingest: the object itself or one entry of an archive.
+struct SourceFile {
+    /// Name recorded in the ingestion log and on every document.
+    file_name: String,
+    /// Key used for rule matching before compression suffixes are removed.
+    object_key: String,
+    /// Key used for rule matching and file type detection.
+    key: String,
+    bucket: String,
+    /// Object metadata; the content type is only kept when it describes this content.
+    metadata: FileMetadata,
+    stream: FileStream,
+}
+
+/// The per-document steps a rule runs between parsing and storage.
+struct DocumentStages {
+    transform: Transform,
+    schema: DocumentSchema,
+}
+
+impl DocumentStages {
+    fn compile(config: &IngestionConfigRule) -> Result<Self, IngestionError> {
+        Ok(Self {
+            transform: Transform::from_config(config.transform.as_ref())?,
+            schema: DocumentSchema::from_config(config.schema.as_ref())?,
+        })
+    }
+
+    /// Transforms `documents` and splits them into those to store and reject records for
+    /// those that fail the transform or the schema. `offset` is the number of documents
+    /// before this batch, so rejects carry the document's 1-based `document_index` among all
+    /// those the parser produced from the file, next to the `source_row` it was read from
+    /// when the parser reports one.
+    fn apply(&self, documents: Vec<serde_json::Value>, offset: usize, file_name: &str, key: &str) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+        let mut accepted = Vec::with_capacity(documents.len());
+        let mut rejected = Vec::new();
+        
+        for (i, mut document) in documents.into_iter().enumerate() {
+            let document_index = offset + i + 1;
+            let source_row = document.as_object_mut()
+                .and_then(|fields| fields.remove(SOURCE_ROW_FIELD))
+                .unwrap_or(serde_json::Value::Null);
+            let original = (!self.transform.is_empty()).then(|| document.clone());
+            let document = match self.transform.apply(document) {
+                Ok(document) => document,
+                Err(e) => {
+                    warn!("Document {} of {} failed the transform: {}", document_index, key, e);
+                    rejected.push(serde_json::json!({
+                        "source_row": source_row,
+                        "document_index": document_index,
+                        "errors": [e],
+                        "document": original,
+                    }));
+                    continue;
+                }
+            };
+            
+            let errors = self.schema.validate(&document);
+            if errors.is_empty() {
+                accepted.push(document);
+            } else {
+                debug!("Document {} of {} failed the schema: {:?}", document_index, key, errors);
+                rejected.push(serde_json::json!({
+                    "source_row": source_row,
+                    "document_index": document_index,
+                    "errors": errors,
+                    "document": document,
+                }));
+            }
+        }
+        
+        (IngestionService::with_file_name(accepted, file_name), IngestionService::with_file_name(rejected, file_name))
+    }
 }
 
 impl IngestionService {
     pub fn new(
         file_fetcher: Arc<dyn FileFetcher>,
+        decompressor: Arc<dyn Decompressor>,
         data_parser: Arc<dyn DataParser>,
         config_repo: Arc<dyn ConfigRepository>,
         data_repo: Arc<dyn DataRepository>,
@@ -26,13 +113,22 @@ impl IngestionService {
     ) -> Self {
         Self {
             file_fetcher,
+            decompressor,
             data_parser,
             config_repo,
             data_repo,
             log_repo,
+            stale_log_timeout: DEFAULT_STALE_LOG_TIMEOUT,
         }
     }
 
+    /// Sets how long a log may stay in progress before a redelivery treats it as failed.
+    /// Keep it above the longest expected ingestion, e.g. the SQS visibility timeout.
+    pub fn with_stale_log_timeout(mut self, timeout: Duration) -> Self {
+        self.stale_log_timeout = timeout;
+        self
+    }
+
     pub async fn process_file(&self, file: FileToProcess) -> Result<(), IngestionError> {
         let start_time = Utc::now();
         let file_name = format!("{}/{}", file.bucket, file.key);
@@ -45,115 +141,510 @@ impl IngestionService {
     async fn process_file_internal(&self, file: &FileToProcess, start_time: DateTime<Utc>) -> Result<(), IngestionError> {
         debug!("File details - bucket: {}, key: {}", file.bucket, file.key);
 
-        // Step 1: Find matching configuration
-        debug!("Step 1: Finding matching configuration for key: {}", file.key);
-        let config = self.find_matching_config(&file.key).await
+        // Step 1: Open S3 object and unwrap any compression
+        debug!("Step 1: Opening S3 stream: {}/{}", file.bucket, file.key);
+        let mut metadata = self.file_fetcher.fetch_metadata(&file.bucket, &file.key).await
+            .unwrap_or_else(|e| {
+                warn!("Failed to fetch metadata for {}/{}, continuing without it: {}", file.bucket, file.key, e);
+                FileMetadata::default()
+            });
+        // Tagging needs its own request and permission; rules on tags then simply do not match
+        if self.config_repo.uses_tags().await? {
+            metadata.tags = self.file_fetcher.fetch_tags(&file.bucket, &file.key).await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to fetch tags for {}/{}, continuing without them: {}", file.bucket, file.key, e);
+                    Default::default()
+                });
+        }
+        let stream = self.file_fetcher.fetch_stream(&file.bucket, &file.key).await
             .map_err(|e| {
-                error!("Failed to find matching config for {}: {}", file.key, e);
+                error!("Failed to open stream for {}/{}: {}", file.bucket, file.key, e);
+                e
+            })?;
+        let opened = self.decompressor.open(&file.key, stream).await
+            .map_err(|e| {
+                error!("Failed to open {}/{}: {}", file.bucket, file.key, e);
                 e
             })?;
-        info!("Found matching config - target table: {}, pattern: {}", config.target_table, config.pattern);
         
-        // Step 2: Fetch file from S3
-        debug!("Step 2: Fetching file from S3: {}/{}", file.bucket, file.key);
-        let file_bytes = self.file_fetcher.fetch_file(&file.bucket, &file.key).await
+        match opened {
+            OpenedFile::Single { key, stream } => {
+                // The content type of a compressed object describes the compressed bytes
+                let mut metadata = metadata;
+                metadata.content_type = metadata.content_type.filter(|_| key == file.key);
+                let source = SourceFile {
+                    file_name: format!("{}/{}", file.bucket, file.key),
+                    object_key: file.key.clone(),
+                    key,
+                    bucket: file.bucket.clone(),
+                    metadata,
+                    stream,
+                };
+                self.process_source(source, start_time).await
+            },
+            OpenedFile::Archive(mut entries) => {
+                info!("Processing entries from archive {}/{}", file.bucket, file.key);
+                let mut total = 0;
+                let mut skipped = 0;
+                let mut failures = Vec::new();
+                
+                while let Some(entry) = entries.next().await {
+                    total += 1;
+                    let entry = match entry {
+                        Ok(entry) => entry,
+                        Err(e) => {
+                            error!("Failed to extract entry from archive {}: {}", file.key, e);
+                            failures.push(e.to_string());
+                            continue;
+                        }
+                    };
+                    let key = format!("{}/{}", file.key, entry.name);
+                    // Entries share the archive's tags and user metadata but have their own size
+                    let entry_metadata = FileMetadata {
+                        content_type: None,
+                        size: Some(entry.size),
+                        ..metadata.clone()
+                    };
+                    let source = SourceFile {
+                        file_name: format!("{}/{}", file.bucket, key),
+                        object_key: key.clone(),
+                        key,
+                        bucket: file.bucket.clone(),
+                        metadata: entry_metadata,
+                        stream: entry.stream,
+                    };
+                    let entry_version = Self::source_version(&source.bucket, &source.object_key, &source.metadata);
+                    let entry_file_name = source.file_name.clone();
+                    match self.process_source(source, Utc::now()).await {
+                        Ok(()) => {},
+                        Err(IngestionError::NoMatchingRule(key)) => {
+                            warn!("Skipping archive entry without a matching rule: {}", key);
+                            skipped += 1;
+                            self.log_skipped(&entry_file_name, entry_version, "No configuration rule matches this archive entry".to_string()).await;
+                        },
+                        Err(e) => {
+                            error!("Failed to process archive entry {}: {}", entry.name, e);
+                            failures.push(format!("{}: {}", entry.name, e));
+                        }
+                    }
+                }
+                
+                if skipped == total {
+                    let message = if total == 0 {
+                        "Archive contains no files".to_string()
+                    } else {
+                        format!("None of the {} entries of the archive matches a configuration rule", total)
+                    };
+                    warn!("Nothing ingested from archive {}/{}: {}", file.bucket, file.key, message);
+                    let archive_version = Self::source_version(&file.bucket, &file.key, &metadata);
+                    self.log_skipped(&format!("{}/{}", file.bucket, file.key), archive_version, message).await;
+                }
+                
+                if failures.is_empty() {
+                    Ok(())
+                } else {
+                    Err(IngestionError::Archive(format!("{} of {} entries in {} failed: {}", 
+                        failures.len(), total, file.key, failures.join("; "))))
+                }
+            }
+        }
+    }
+    
+    async fn process_source(&self, source: SourceFile, start_time: DateTime<Utc>) -> Result<(), IngestionError> {
+        // Step 2: Find matching configuration
+        debug!("Step 2: Finding matching configuration for key: {}", source.key);
+        let mut configs = self.find_source_configs(&source).await
             .map_err(|e| {
-                error!("Failed to fetch file {}/{}: {}", file.bucket, file.key, e);
+                error!("Failed to find matching config for {}: {}", source.key, e);
                 e
             })?;
-        info!("Successfully fetched file, size: {} bytes", file_bytes.len());
         
-        // Step 3: Extract file type
-        let file_type = self.extract_file_type(&file.key);
-        debug!("Step 3: Detected file type: {}", file_type);
+        if configs.len() == 1 {
+            let config = configs.remove(0);
+            info!("Found matching config - target table: {}, pattern: {}", config.target_table, config.pattern);
+            return self.process_target(source, &config, start_time).await;
+        }
+        
+        // Each target parses the content with its own settings, so the stream is spooled to disk
+        // once and every target reads the spooled file from the start
+        info!("Fanning out {} to {} targets", source.file_name, configs.len());
+        let spool = SpoolFile::new("fanout");
+        let spool_error = |e: std::io::Error| {
+            error!("Failed to spool file {}: {}", source.file_name, e);
+            IngestionError::S3(e.to_string())
+        };
+        let mut file = tokio::fs::OpenOptions::new().write(true).create_new(true).open(spool.path()).await
+            .map_err(spool_error)?;
+        let mut stream = source.stream;
+        let spooled = tokio::io::copy(&mut stream, &mut file).await.map_err(spool_error)?;
+        file.flush().await.map_err(spool_error)?;
+        drop(file);
+        debug!("Spooled {} ({} bytes) for fan-out", source.file_name, spooled);
+        
+        let total = configs.len();
+        let mut failures = Vec::new();
+        for config in configs {
+            info!("Processing fan-out target - target table: {}, pattern: {}", config.target_table, config.pattern);
+            let stream = match tokio::fs::File::open(spool.path()).await {
+                Ok(file) => file,
+                Err(e) => {
+                    failures.push(format!("{}: {}", config.target_table, spool_error(e)));
+                    continue;
+                },
+            };
+            let target_source = SourceFile {
+                file_name: source.file_name.clone(),
+                object_key: source.object_key.clone(),
+                key: source.key.clone(),
+                bucket: source.bucket.clone(),
+                metadata: source.metadata.clone(),
+                stream: Box::pin(stream),
+            };
+            if let Err(e) = self.process_target(target_source, &config, start_time).await {
+                error!("Failed to process {} for target {}: {}", source.file_name, config.target_table, e);
+                failures.push(format!("{}: {}", config.target_table, e));
+            }
+        }
+        
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(IngestionError::Fanout(format!("{} of {} targets for {} failed: {}", 
+                failures.len(), total, source.file_name, failures.join("; "))))
+        }
+    }
+    
+    /// Parses `source` with one rule's settings and stores the documents in its target table.
+    async fn process_target(&self, mut source: SourceFile, config: &IngestionConfigRule, start_time: DateTime<Utc>) -> Result<(), IngestionError> {
+        // Redelivered events for a version this target already ingested are handled per the rule's policy
+        let version = Self::source_version(&source.bucket, &source.object_key, &source.metadata);
+        let mut previous_logs = self.find_previous_logs(&version, config).await?;
+        self.fail_stale_logs(&mut previous_logs).await;
+        if config.on_duplicate == DuplicatePolicy::Skip {
+            // Logs left without an end time by a crashed task were failed above, so an open log is still running
+            let previous = previous_logs.iter().find(|(_, log)| log.status == IngestionStatus::Success || log.end_time.is_none());
+            if let Some((log_id, log)) = previous {
+                let state = if log.end_time.is_some() { "already ingested" } else { "being ingested" };
+                info!("⏭️ Skipping {} for {}: {} by log {}", source.file_name, config.target_table, state, log_id);
+                return Ok(());
+            }
+        }
+        
+        // Step 3: Determine file type
+        let file_type = match &config.file_type {
+            Some(file_type) => {
+                debug!("Step 3: Using file type '{}' from rule", file_type);
+                file_type.to_lowercase()
+            },
+            None => {
+                let (head, stream) = Self::peek(source.stream).await
+                    .map_err(|e| {
+                        error!("Failed to read file {}: {}", source.file_name, e);
+                        IngestionError::S3(e.to_string())
+                    })?;
+                source.stream = stream;
+                let file_type = detect_file_type(&source.key, source.metadata.content_type.as_deref(), &head, self.data_parser.as_ref());
+                debug!("Step 3: Detected file type: {}", file_type);
+                file_type
+            }
+        };
+        
+        if file_type.is_empty() {
+            warn!("Could not determine file type for: {}", source.key);
+        }
+        
+        // Every target gets a log as soon as it is known, so parse failures are recorded too
+        let log_id = self.create_log(&source.file_name, config, &version, start_time).await?;
+        
+        let mut counts = DocumentCounts::default();
+        let processing_result = match DocumentStages::compile(config) {
+            Err(e) => {
+                error!("Invalid transform or schema in rule '{}': {}", config.pattern, e);
+                Err(e)
+            },
+            Ok(stages) => match self.delete_earlier_documents(config, &previous_logs).await {
+                Err(e) => Err(e),
+                Ok(()) if self.data_parser.supports_streaming(&file_type, config.parser_config.as_ref()) => {
+                    self.process_file_streaming(source, config, &stages, &file_type, &log_id, &mut counts).await
+                },
+                Ok(()) => self.process_file_buffered(source, config, &stages, &file_type, &log_id, &mut counts).await,
+            },
+        };
+        let processing_result = processing_result.and_then(|_| Self::check_reject_ratio(config, &counts));
+        
+        self.finish_log(&log_id, &processing_result, counts).await;
+        
+        processing_result
+    }
+
+    async fn process_file_buffered(&self, source: SourceFile, config: &IngestionConfigRule, stages: &DocumentStages, file_type: &str, log_id: &str, counts: &mut DocumentCounts) -> Result<(), IngestionError> {
+        // Step 4: Read and parse file content
+        let mut file_bytes = Vec::new();
+        let mut stream = source.stream;
+        stream.read_to_end(&mut file_bytes).await
+            .map_err(|e| {
+                error!("Failed to read file {}: {}", source.file_name, e);
+                IngestionError::S3(e.to_string())
+            })?;
+        info!("Successfully fetched file, size: {} bytes", file_bytes.len());
         
-        // Step 4: Parse file content
         debug!("Step 4: Parsing file content with type: {} and config: {:?}", file_type, config.parser_config);
-        let documents = self.data_parser.parse_with_config(&file_bytes, &file_type, config.parser_config.as_ref()).await
+        let documents = self.data_parser.parse_with_config(&file_bytes, file_type, config.parser_config.as_ref()).await
             .map_err(|e| {
-                error!("Failed to parse file {}: {}", file.key, e);
+                error!("Failed to parse file {}: {}", source.key, e);
                 e
             })?;
         info!("Successfully parsed {} documents from file", documents.len());
         
-        // Step 5: Add file_name to each document and store
-        debug!("Step 5: Adding file_name and storing {} documents to table: {}", documents.len(), config.target_table);
-        let file_name = format!("{}/{}", file.bucket, file.key);
-        let mut documents_with_filename: Vec<serde_json::Value> = documents
+        // Step 5: Transform, validate, add file_name and store
+        let (accepted, rejected) = stages.apply(documents, 0, &source.file_name, &source.key);
+        debug!("Step 5: Storing {} documents to table: {}", accepted.len(), config.target_table);
+        self.store(config, &accepted, &rejected, log_id, counts).await
+            .map_err(|e| {
+                error!("Failed to store documents for {}: {}", source.key, e);
+                e
+            })?;
+        
+        info!("✅ Successfully processed file {} - {} documents stored in {}, {} rejected", 
+            source.file_name, counts.accepted, config.target_table, counts.rejected);
+        Ok(())
+    }
+
+    async fn process_file_streaming(&self, source: SourceFile, config: &IngestionConfigRule, stages: &DocumentStages, file_type: &str, log_id: &str, counts: &mut DocumentCounts) -> Result<(), IngestionError> {
+        // Step 4: Start incremental parsing
+        debug!("Step 4: Streaming parse with type: {} and config: {:?}", file_type, config.parser_config);
+        let mut batches = self.data_parser.parse_stream(source.stream, file_type, config.parser_config.as_ref()).await
+            .map_err(|e| {
+                error!("Failed to start parsing file {}: {}", source.key, e);
+                e
+            })?;
+        
+        // Step 5: Store each batch as it is parsed
+        let file_name = &source.file_name;
+        let mut batch_count = 0;
+        let mut document_count = 0;
+        
+        while let Some(batch) = batches.next().await {
+            let documents = batch.map_err(|e| {
+                error!("Failed to parse file {} after {} documents: {}", source.key, document_count, e);
+                e
+            })?;
+            batch_count += 1;
+            
+            let batch_size = documents.len();
+            let (accepted, rejected) = stages.apply(documents, document_count, file_name, &source.key);
+            debug!("Step 5: Storing batch {} ({} documents, {} rejected) to table: {}", batch_count, accepted.len(), rejected.len(), config.target_table);
+            self.store(config, &accepted, &rejected, log_id, counts).await
+                .map_err(|e| {
+                    error!("Failed to store batch {} for {}: {}", batch_count, source.key, e);
+                    e
+                })?;
+            document_count += batch_size;
+        }
+        
+        info!("✅ Successfully streamed file {} - {} documents stored in {} batches to {}, {} rejected", 
+            file_name, counts.accepted, batch_count, config.target_table, counts.rejected);
+        Ok(())
+    }
+
+    /// Stores valid documents in the rule's table and rejected ones in `<target_table>_rejects`.
+    async fn store(&self, config: &IngestionConfigRule, accepted: &[serde_json::Value], rejected: &[serde_json::Value], log_id: &str, counts: &mut DocumentCounts) -> Result<(), IngestionError> {
+        if !accepted.is_empty() {
+            self.data_repo.insert_documents(&config.target_table, accepted, log_id).await?;
+            counts.accepted += accepted.len() as u64;
+        }
+        if !rejected.is_empty() {
+            let rejects_table = format!("{}_rejects", config.target_table);
+            warn!("Storing {} documents that failed the schema in {}", rejected.len(), rejects_table);
+            self.data_repo.insert_documents(&rejects_table, rejected, log_id).await?;
+            counts.rejected += rejected.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn check_reject_ratio(config: &IngestionConfigRule, counts: &DocumentCounts) -> Result<(), IngestionError> {
+        match config.max_reject_ratio {
+            Some(max_ratio) if counts.reject_ratio() > max_ratio => {
+                error!("{} of {} documents rejected for {}, more than the allowed ratio {}",
+                    counts.rejected, counts.accepted + counts.rejected, config.target_table, max_ratio);
+                Err(IngestionError::Rejects(format!("{} of {} documents rejected, more than the allowed ratio {}",
+                    counts.rejected, counts.accepted + counts.rejected, max_ratio)))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn with_file_name(documents: Vec<serde_json::Value>, file_name: &str) -> Vec<serde_json::Value> {
+        documents
             .into_iter()
             .map(|mut doc| {
                 if let serde_json::Value::Object(ref mut map) = doc {
-                    map.insert("file_name".to_string(), serde_json::Value::String(file_name.clone()));
+                    map.insert("file_name".to_string(), serde_json::Value::String(file_name.to_string()));
                 }
                 doc
             })
-            .collect();
+            .collect()
+    }
+
+    /// Earlier logs of `version` for the rule's table; none when the rule appends anyway
+    /// or the source reports neither a version id nor an ETag.
+    async fn find_previous_logs(&self, version: &SourceVersion, config: &IngestionConfigRule) -> Result<Vec<(String, IngestionLog)>, IngestionError> {
+        if config.on_duplicate == DuplicatePolicy::Append {
+            return Ok(Vec::new());
+        }
+        if !version.is_identified() {
+            warn!("No version id or ETag for s3://{}/{}, cannot detect duplicate deliveries", version.bucket, version.key);
+            return Ok(Vec::new());
+        }
         
+        self.log_repo.find_logs(version, &config.target_table).await
+            .map_err(|e| {
+                error!("Failed to look up earlier ingestions of {}: {}", version.key, e);
+                e
+            })
+    }
+
+    /// Marks logs that have been open for longer than the stale log timeout as failed: the task that
+    /// opened them stopped before finishing, so their documents are partial.
+    async fn fail_stale_logs(&self, previous_logs: &mut [(String, IngestionLog)]) {
+        let now = Utc::now();
+        for (log_id, log) in previous_logs.iter_mut().filter(|(_, log)| log.end_time.is_none()) {
+            let open_for = (now - log.start_time).to_std().unwrap_or_default();
+            if open_for < self.stale_log_timeout {
+                continue;
+            }
+            
+            warn!("Log {} of {} has been in progress for {:?}, marking it failed", log_id, log.file_name, open_for);
+            let message = format!("No end time after {:?}, the ingestion was abandoned", self.stale_log_timeout);
+            let counts = DocumentCounts { accepted: log.accepted_count, rejected: log.rejected_count };
+            if let Err(e) = self.log_repo.update_log(log_id, now, IngestionStatus::Failed, Some(message), counts).await {
+                error!("Failed to mark stale log {} as failed: {}", log_id, e);
+            }
+            log.status = IngestionStatus::Failed;
+            log.end_time = Some(now);
+        }
+    }
+
+    /// Deletes what earlier ingestions stored in the target and rejects tables: all of them with
+    /// the replace policy, and the failed ones with the skip policy, so that a retry starts clean.
+    async fn delete_earlier_documents(&self, config: &IngestionConfigRule, previous_logs: &[(String, IngestionLog)]) -> Result<(), IngestionError> {
+        let rejects_table = format!("{}_rejects", config.target_table);
+        for (log_id, log) in previous_logs {
+            let action = match config.on_duplicate {
+                DuplicatePolicy::Replace => "Replacing",
+                DuplicatePolicy::Skip if log.status == IngestionStatus::Failed => "Retrying",
+                _ => continue,
+            };
+            let deleted = self.data_repo.delete_documents(&config.target_table, log_id).await?
+                + self.data_repo.delete_documents(&rejects_table, log_id).await?;
+            info!("{} ingestion {} of {}: deleted {} documents", action, log_id, config.target_table, deleted);
+        }
+        Ok(())
+    }
+
+    async fn create_log(&self, file_name: &str, config: &IngestionConfigRule, version: &SourceVersion, start_time: DateTime<Utc>) -> Result<String, IngestionError> {
         // Create initial log entry to get log_id
         let log = IngestionLog {
-            file_name: format!("{}/{}", file.bucket, file.key),
+            file_name: file_name.to_string(),
+            target_table: config.target_table.clone(),
+            rule_id: config.rule_id.clone(),
+            rule_version: config.version,
+            accepted_count: 0,
+            rejected_count: 0,
+            source: Some(version.clone()),
             start_time,
             end_time: None,
-            status: IngestionStatus::Success,
+            status: IngestionStatus::InProgress,
             message: None,
         };
-        let log_id = self.log_repo.insert_log(&log).await
+        self.log_repo.insert_log(&log).await
             .map_err(|e| {
-                error!("Failed to create log entry for {}: {}", file.key, e);
+                error!("Failed to create log entry for {}: {}", file_name, e);
                 e
-            })?;
-        
-        let processing_result: Result<(), IngestionError> = async {
-            let _inserted_ids = self.data_repo.insert_documents(&config.target_table, &documents_with_filename, &log_id).await
-                .map_err(|e| {
-                    error!("Failed to store documents for {}: {}", file.key, e);
-                    e
-                })?;
-            
-            info!("✅ Successfully processed file {}/{} - {} documents stored in {}", 
-                file.bucket, file.key, documents_with_filename.len(), config.target_table);
-            Ok::<(), IngestionError>(())
-        }.await;
-        
+            })
+    }
+
+    /// Records that `file_name` was read but nothing was ingested from it.
+    async fn log_skipped(&self, file_name: &str, version: SourceVersion, message: String) {
+        let now = Utc::now();
+        let log = IngestionLog {
+            file_name: file_name.to_string(),
+            target_table: String::new(),
+            rule_id: None,
+            rule_version: 0,
+            accepted_count: 0,
+            rejected_count: 0,
+            source: Some(version),
+            start_time: now,
+            end_time: Some(now),
+            status: IngestionStatus::Skipped,
+            message: Some(message),
+        };
+        if let Err(e) = self.log_repo.insert_log(&log).await {
+            error!("Failed to log skipped file {}: {}", file_name, e);
+        }
+    }
+
+    async fn finish_log(&self, log_id: &str, processing_result: &Result<(), IngestionError>, counts: DocumentCounts) {
         // Update log with final status
-        let (status, message) = match &processing_result {
+        let (status, message) = match processing_result {
             Ok(_) => (IngestionStatus::Success, Some("File processed successfully".to_string())),
             Err(e) => (IngestionStatus::Failed, Some(e.to_string())),
         };
         
-        let _ = self.log_repo.update_log(&log_id, Utc::now(), status, message).await;
-        
-        processing_result
+        let _ = self.log_repo.update_log(log_id, Utc::now(), status, message, counts).await;
     }
 
-    async fn find_matching_config(&self, s3_key: &str) -> Result<IngestionConfigRule, IngestionError> {
-        debug!("Searching for configuration rule matching key: {}", s3_key);
+    fn source_version(bucket: &str, key: &str, metadata: &FileMetadata) -> SourceVersion {
+        SourceVersion {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            version_id: metadata.version_id.clone(),
+            etag: metadata.etag.clone(),
+        }
+    }
+
+    /// Rules may target the object key (`orders.csv.gz`) or the decompressed key (`orders.csv`).
+    async fn find_source_configs(&self, source: &SourceFile) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        if source.object_key != source.key {
+            let object = FileToProcess { bucket: source.bucket.clone(), key: source.object_key.clone() };
+            let configs = self.config_repo.get_configs_for_file(&object, &source.metadata).await?;
+            if !configs.is_empty() {
+                debug!("Matched {} rules on object key: {}", configs.len(), source.object_key);
+                return Ok(configs);
+            }
+        }
+        let file = FileToProcess { bucket: source.bucket.clone(), key: source.key.clone() };
+        self.find_matching_configs(&file, &source.metadata).await
+    }
+
+    async fn find_matching_configs(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        debug!("Searching for configuration rules matching {}/{}", file.bucket, file.key);
         
-        match self.config_repo.get_config_for_key(s3_key).await {
-            Ok(Some(config)) => {
-                debug!("Found matching config rule: pattern='{}', target_table='{}'", 
-                    config.pattern, config.target_table);
-                Ok(config)
+        match self.config_repo.get_configs_for_file(file, metadata).await {
+            Ok(configs) if configs.is_empty() => {
+                warn!("No configuration rule found for key: {}", file.key);
+                Err(IngestionError::NoMatchingRule(file.key.clone()))
             },
-            Ok(None) => {
-                warn!("No configuration rule found for key: {}", s3_key);
-                Err(IngestionError::NoMatchingRule(s3_key.to_string()))
+            Ok(configs) => {
+                for config in &configs {
+                    debug!("Found matching config rule: pattern='{}', target_table='{}'", 
+                        config.pattern, config.target_table);
+                }
+                Ok(configs)
             },
             Err(e) => {
-                error!("Error retrieving configuration for key {}: {}", s3_key, e);
+                error!("Error retrieving configuration for key {}: {}", file.key, e);
                 Err(e)
             }
         }
     }
 
-    fn extract_file_type(&self, key: &str) -> String {
-        let file_type = key.split('.').last().unwrap_or("").to_lowercase();
-        debug!("Extracted file type '{}' from key: {}", file_type, key);
-        
-        if file_type.is_empty() {
-            warn!("No file extension found in key: {}", key);
-        }
-        
-        file_type
+    /// Reads the leading bytes for sniffing and returns a stream that still yields them.
+    async fn peek(mut stream: FileStream) -> std::io::Result<(Vec<u8>, FileStream)> {
+        let mut head = Vec::new();
+        stream.as_mut().take(SNIFF_BYTES as u64).read_to_end(&mut head).await?;
+        let stream: FileStream = Box::pin(std::io::Cursor::new(head.clone()).chain(stream));
+        Ok((head, stream))
     }
 }
\ No newline at end of file
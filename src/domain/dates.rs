@@ -0,0 +1,27 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+const DATETIME_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Parses a date or datetime and renders it as ISO 8601.
+pub fn parse_date(raw: &str, format: Option<&str>) -> Option<String> {
+    if let Some(format) = format {
+        if let Ok(dt) = DateTime::parse_from_str(raw, format) {
+            return Some(dt.to_rfc3339());
+        }
+        if let Ok(dt) = NaiveDateTime::parse_from_str(raw, format) {
+            return Some(dt.format("%Y-%m-%dT%H:%M:%S").to_string());
+        }
+        return NaiveDate::parse_from_str(raw, format).ok().map(|d| d.format(DATE_FORMAT).to_string());
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.to_rfc3339());
+    }
+    for format in DATETIME_FORMATS {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(raw, format) {
+            return Some(dt.format("%Y-%m-%dT%H:%M:%S").to_string());
+        }
+    }
+    NaiveDate::parse_from_str(raw, DATE_FORMAT).ok().map(|d| d.format(DATE_FORMAT).to_string())
+}
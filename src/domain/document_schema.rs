@@ -0,0 +1,36 @@
+use jsonschema::Validator;
+use serde_json::Value;
+use crate::domain::error::IngestionError;
+
+/// The JSON Schema of a rule, checked against every document before it is stored.
+#[derive(Default)]
+pub struct DocumentSchema {
+    validator: Option<Validator>,
+}
+
+impl DocumentSchema {
+    /// Compiles a rule's `schema`; the draft is taken from `$schema`, defaulting to 2020-12.
+    pub fn from_config(schema: Option<&Value>) -> Result<Self, IngestionError> {
+        let validator = match schema {
+            None | Some(Value::Null) => None,
+            Some(schema) => Some(jsonschema::validator_for(schema)
+                .map_err(|e| IngestionError::Config(format!("invalid schema: {}", e)))?),
+        };
+        Ok(Self { validator })
+    }
+
+    /// Every way `document` violates the schema, prefixed with the path of the offending
+    /// value; empty when the document is valid.
+    pub fn validate(&self, document: &Value) -> Vec<String> {
+        let Some(validator) = &self.validator else {
+            return Vec::new();
+        };
+
+        validator.iter_errors(document)
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                format!("{}: {}", if path.is_empty() { "/" } else { &path }, e)
+            })
+            .collect()
+    }
+}
@@ -6,10 +6,20 @@ pub enum IngestionError {
     Config(String),
     #[error("S3 error: {0}")]
     S3(String),
+    #[error("Decompression error: {0}")]
+    Decompression(String),
     #[error("Parsing error: {0}")]
     Parse(String),
+    #[error("Transform error: {0}")]
+    Transform(String),
+    #[error("Too many rejected documents: {0}")]
+    Rejects(String),
     #[error("Database error: {0}")]
     Database(String),
     #[error("No matching configuration rule found for key: {0}")]
     NoMatchingRule(String),
+    #[error("Archive entries failed: {0}")]
+    Archive(String),
+    #[error("Fan-out targets failed: {0}")]
+    Fanout(String),
 }
\ No newline at end of file
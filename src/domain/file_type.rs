@@ -0,0 +1,128 @@
+use tracing::debug;
+use crate::domain::ports::DataParser;
+
+/// Number of leading bytes inspected when sniffing content.
+pub const SNIFF_BYTES: usize = 8192;
+
+const SPREADSHEET_ZIP_EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xlsb", "ods"];
+
+/// Works out the parser file type for an object.
+///
+/// Sources are consulted from most to least reliable: binary magic bytes, the S3
+/// `Content-Type`, the key extension, then a probe of the leading text. Content types
+/// and extensions are only trusted when `parser` handles them. Compressed objects are
+/// unwrapped before detection, so `head` is always the decompressed content.
+pub fn detect_file_type(key: &str, content_type: Option<&str>, head: &[u8], parser: &dyn DataParser) -> String {
+    let extension = extension_of(key);
+
+    if let Some(file_type) = from_magic(head, extension.as_deref()) {
+        debug!("Detected file type '{}' from magic bytes of {}", file_type, key);
+        return file_type;
+    }
+
+    if let Some(file_type) = content_type.and_then(|ct| parser.file_type_for_content_type(ct)) {
+        debug!("Detected file type '{}' from content type {:?} of {}", file_type, content_type, key);
+        return refine_json(file_type, head);
+    }
+
+    let known_types = parser.file_types();
+    if let Some(ext) = extension.as_deref().filter(|ext| known_types.iter().any(|t| t == ext)) {
+        debug!("Detected file type '{}' from extension of {}", ext, key);
+        return refine_json(ext.to_string(), head);
+    }
+
+    if let Some(file_type) = probe_text(head) {
+        debug!("Detected file type '{}' by probing content of {}", file_type, key);
+        return file_type.to_string();
+    }
+
+    extension.unwrap_or_default()
+}
+
+/// Lowercased extension of the last path segment, if any.
+pub fn extension_of(key: &str) -> Option<String> {
+    let file_name = key.rsplit('/').next().unwrap_or(key);
+    file_name.rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .filter(|ext| !ext.is_empty())
+}
+
+fn from_magic(head: &[u8], extension: Option<&str>) -> Option<String> {
+    let file_type = if head.starts_with(b"PAR1") {
+        "parquet"
+    } else if head.starts_with(b"%PDF-") {
+        "pdf"
+    } else if head.starts_with(b"Obj\x01") {
+        "avro"
+    } else if head.starts_with(&[0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1]) {
+        "xls"
+    } else if head.starts_with(b"PK\x03\x04") {
+        // xlsx, xlsm and ods are all zip containers; keep the specific extension when present
+        return Some(extension
+            .filter(|ext| SPREADSHEET_ZIP_EXTENSIONS.contains(ext))
+            .unwrap_or("xlsx")
+            .to_string());
+    } else {
+        return None;
+    };
+    Some(file_type.to_string())
+}
+
+/// A `.json` object is often newline-delimited JSON in disguise.
+fn refine_json(file_type: String, head: &[u8]) -> String {
+    if file_type == "json" && looks_like_ndjson(&text_of(head)) {
+        debug!("JSON content has one value per line, treating it as NDJSON");
+        return "ndjson".to_string();
+    }
+    file_type
+}
+
+fn probe_text(head: &[u8]) -> Option<&'static str> {
+    if head.is_empty() || head.contains(&0) {
+        return None;
+    }
+
+    let text = text_of(head);
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') {
+        return Some("json");
+    }
+    if trimmed.starts_with('{') {
+        return Some(if looks_like_ndjson(trimmed) { "ndjson" } else { "json" });
+    }
+    if trimmed.starts_with('<') {
+        return Some("xml");
+    }
+    if looks_like_csv(trimmed) {
+        return Some("csv");
+    }
+    Some("txt")
+}
+
+fn text_of(head: &[u8]) -> String {
+    let head = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+    String::from_utf8_lossy(head).into_owned()
+}
+
+/// At least two lines, the first of which is a complete JSON object on its own.
+fn looks_like_ndjson(text: &str) -> bool {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    match (lines.next(), lines.next()) {
+        (Some(first), Some(second)) => {
+            second.starts_with('{') && serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(first).is_ok()
+        },
+        _ => false,
+    }
+}
+
+/// The first lines share a delimiter with the same count on each line.
+fn looks_like_csv(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).take(5).collect();
+    // The last line may be cut off at the sniff boundary
+    let complete = if lines.len() > 2 { &lines[..lines.len() - 1] } else { &lines[..] };
+
+    [',', ';', '\t', '|'].iter().any(|&delimiter| {
+        let count = complete.first().map(|line| line.matches(delimiter).count()).unwrap_or(0);
+        count > 0 && complete.iter().all(|line| line.matches(delimiter).count() == count)
+    })
+}
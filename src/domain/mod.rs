@@ -1,3 +1,8 @@
+pub mod dates;
+pub mod document_schema;
 pub mod error;
+pub mod file_type;
 pub mod models;
-pub mod ports;
\ No newline at end of file
+pub mod ports;
+pub mod rule_matching;
+pub mod transform;
\ No newline at end of file
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -6,6 +7,162 @@ pub struct IngestionConfigRule {
     pub pattern: String,
     pub target_table: String,
     pub parser_config: Option<serde_json::Value>,
+    /// Forces the parser file type instead of detecting it from the object.
+    pub file_type: Option<String>,
+    /// Precedence when several rules match a key; higher wins.
+    #[serde(default)]
+    pub priority: i32,
+    /// Also apply this rule when a higher-precedence rule matches the same key.
+    #[serde(default)]
+    pub fanout: bool,
+    /// Conditions on the object besides its key; all must hold for the rule to match.
+    #[serde(default)]
+    pub matchers: RuleMatchers,
+    /// Id shared by every version of a rule; rules without one are not versioned.
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// Of the versions of a `rule_id` in effect, the highest applies; the others are kept as history.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// A disabled version takes effect like any other but matches nothing, switching the rule off.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Start of the period this version is in effect, inclusive.
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    pub valid_from: Option<DateTime<Utc>>,
+    /// End of the period this version is in effect, exclusive.
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Steps applied to every parsed document before it is stored; see `domain::transform`.
+    #[serde(default)]
+    pub transform: Option<serde_json::Value>,
+    /// JSON Schema every document must satisfy after the transform; others go to `<target_table>_rejects`.
+    #[serde(default)]
+    pub schema: Option<serde_json::Value>,
+    /// Fraction of documents (0 to 1) that may be rejected before the file is marked failed.
+    #[serde(default)]
+    pub max_reject_ratio: Option<f64>,
+    /// What to do with a file version this rule has already ingested.
+    #[serde(default)]
+    pub on_duplicate: DuplicatePolicy,
+}
+
+/// A validity timestamp as an RFC 3339 string or a BSON date, which reaches serde as
+/// extended JSON (`{"$date": ...}`) from both the Mongo driver and relaxed extended JSON.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Timestamp {
+    Text(DateTime<Utc>),
+    Date {
+        #[serde(rename = "$date")]
+        date: BsonDate,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BsonDate {
+    Text(DateTime<Utc>),
+    Millis(i64),
+    Long {
+        #[serde(rename = "$numberLong")]
+        millis: String,
+    },
+}
+
+fn deserialize_timestamp<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    use serde::de::Error;
+
+    let millis = match Option::<Timestamp>::deserialize(deserializer).map_err(|_| {
+        D::Error::custom("expected an RFC 3339 timestamp string or a BSON date")
+    })? {
+        None => return Ok(None),
+        Some(Timestamp::Text(time)) | Some(Timestamp::Date { date: BsonDate::Text(time) }) => return Ok(Some(time)),
+        Some(Timestamp::Date { date: BsonDate::Millis(millis) }) => millis,
+        Some(Timestamp::Date { date: BsonDate::Long { millis } }) => millis.parse().map_err(D::Error::custom)?,
+    };
+    DateTime::from_timestamp_millis(millis)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("BSON date {} is out of range", millis)))
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for IngestionConfigRule {
+    fn default() -> Self {
+        Self {
+            pattern: String::new(),
+            target_table: String::new(),
+            parser_config: None,
+            file_type: None,
+            priority: 0,
+            fanout: false,
+            matchers: RuleMatchers::default(),
+            rule_id: None,
+            version: default_version(),
+            enabled: default_enabled(),
+            valid_from: None,
+            valid_until: None,
+            transform: None,
+            schema: None,
+            max_reject_ratio: None,
+            on_duplicate: DuplicatePolicy::default(),
+        }
+    }
+}
+
+impl IngestionConfigRule {
+    /// A rule storing keys matching `pattern` in `target_table`, with every option at its default.
+    pub fn new(pattern: impl Into<String>, target_table: impl Into<String>) -> Self {
+        Self { pattern: pattern.into(), target_table: target_table.into(), ..Default::default() }
+    }
+
+    /// Whether this version's validity period includes `now`.
+    pub fn is_in_effect(&self, now: DateTime<Utc>) -> bool {
+        self.valid_from.is_none_or(|from| now >= from) && self.valid_until.is_none_or(|until| now < until)
+    }
+}
+
+/// How a rule treats an object version that already has a successful ingestion log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Leave the stored documents as they are and do nothing.
+    #[default]
+    Skip,
+    /// Delete the documents of the earlier ingestions, then ingest again.
+    Replace,
+    /// Ingest again alongside the earlier documents.
+    Append,
+}
+
+/// Optional conditions a file must meet, in addition to the key `pattern`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleMatchers {
+    /// Regex matched against the bucket name.
+    #[serde(default)]
+    pub bucket: Option<String>,
+    /// Regex matched against the object's `Content-Type`.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Object tags that must be present with exactly these values.
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// User metadata (`x-amz-meta-*`, without the prefix) that must be present with exactly these values.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Smallest matching object size in bytes, inclusive.
+    #[serde(default)]
+    pub min_size: Option<u64>,
+    /// Largest matching object size in bytes, inclusive.
+    #[serde(default)]
+    pub max_size: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -14,17 +171,87 @@ pub struct FileToProcess {
     pub key: String,
 }
 
+/// Object metadata from the source, used for rule matching and file type detection.
+#[derive(Debug, Clone, Default)]
+pub struct FileMetadata {
+    pub content_type: Option<String>,
+    /// Size in bytes.
+    pub size: Option<u64>,
+    pub tags: HashMap<String, String>,
+    /// User-defined metadata, keyed without the `x-amz-meta-` prefix.
+    pub user_metadata: HashMap<String, String>,
+    /// Version id of the object in a versioned bucket.
+    pub version_id: Option<String>,
+    /// ETag of the object, without the surrounding quotes.
+    pub etag: Option<String>,
+}
+
+/// The object version a file was read from, recorded on its logs to recognise
+/// redelivered events. Archive entries use the archive's version and ETag with the
+/// entry path appended to the key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceVersion {
+    pub bucket: String,
+    pub key: String,
+    pub version_id: Option<String>,
+    pub etag: Option<String>,
+}
+
+impl SourceVersion {
+    /// Whether the source reported enough to tell one version of the object from another.
+    pub fn is_identified(&self) -> bool {
+        self.version_id.is_some() || self.etag.is_some()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IngestionLog {
     pub file_name: String,
+    /// Table the documents were stored in; a fanned-out file has one log per target.
+    #[serde(default)]
+    pub target_table: String,
+    /// Id of the rule that processed the file, if the rule has one.
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// Version of the rule that processed the file.
+    #[serde(default = "default_version")]
+    pub rule_version: u32,
+    /// Documents stored in the target table.
+    #[serde(default)]
+    pub accepted_count: u64,
+    /// Documents that failed the rule's schema and were stored in the rejects table.
+    #[serde(default)]
+    pub rejected_count: u64,
+    /// Object version the file was read from.
+    #[serde(default)]
+    pub source: Option<SourceVersion>,
     pub start_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
     pub status: IngestionStatus,
     pub message: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IngestionStatus {
+    /// The file is being ingested; replaced by the final status when it finishes.
+    InProgress,
     Success,
     Failed,
+    /// Nothing was ingested because no rule matched, e.g. for an archive entry.
+    Skipped,
+}
+
+/// How many documents of a file were stored and rejected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DocumentCounts {
+    pub accepted: u64,
+    pub rejected: u64,
+}
+
+impl DocumentCounts {
+    /// Rejected documents as a fraction of all documents, 0 when there were none.
+    pub fn reject_ratio(&self) -> f64 {
+        let total = self.accepted + self.rejected;
+        if total == 0 { 0.0 } else { self.rejected as f64 / total as f64 }
+    }
 }
\ No newline at end of file
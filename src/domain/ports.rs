@@ -1,30 +1,122 @@
+use std::collections::HashMap;
+use std::pin::Pin;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use crate::domain::{error::IngestionError, models::{IngestionConfigRule, IngestionLog, IngestionStatus}};
+use futures_util::stream::BoxStream;
+use tokio::io::AsyncRead;
+use crate::domain::{error::IngestionError, models::{DocumentCounts, FileMetadata, FileToProcess, IngestionConfigRule, IngestionLog, IngestionStatus, SourceVersion}};
+
+/// Raw file content read incrementally from the source.
+pub type FileStream = Pin<Box<dyn AsyncRead + Send>>;
+
+/// Field a parser may set on a document to the 1-based line (text formats) or row
+/// (spreadsheets) the document was read from. The ingestion service removes it before
+/// the document is transformed and stored, and records it on rejects as `source_row`.
+pub const SOURCE_ROW_FIELD: &str = "_source_row";
+
+/// Parsed documents yielded in bounded batches.
+pub type DocumentBatchStream = BoxStream<'static, Result<Vec<serde_json::Value>, IngestionError>>;
+
+/// A fetched object after compression has been handled.
+pub enum OpenedFile {
+    /// A single file, decompressed if needed; `key` has compression suffixes removed.
+    Single { key: String, stream: FileStream },
+    /// The entries of an archive, extracted one at a time as the stream is polled.
+    Archive(ArchiveEntries),
+}
+
+/// A file extracted from an archive, ingested as its own file.
+///
+/// Its content must be read to the end or dropped before the next entry is requested.
+pub struct ArchiveEntry {
+    pub name: String,
+    /// Uncompressed size declared by the archive.
+    pub size: u64,
+    pub stream: FileStream,
+}
+
+/// Entries of an archive in order; an error item fails one entry, or ends the stream
+/// when the archive cannot be read any further.
+pub type ArchiveEntries = BoxStream<'static, Result<ArchiveEntry, IngestionError>>;
 
 #[async_trait]
 pub trait FileFetcher: Send + Sync {
     async fn fetch_file(&self, bucket: &str, key: &str) -> Result<Vec<u8>, IngestionError>;
+
+    async fn fetch_stream(&self, bucket: &str, key: &str) -> Result<FileStream, IngestionError> {
+        let bytes = self.fetch_file(bucket, key).await?;
+        Ok(Box::pin(std::io::Cursor::new(bytes)))
+    }
+
+    /// Everything in `FileMetadata` except `tags`, which need a request of their own.
+    async fn fetch_metadata(&self, _bucket: &str, _key: &str) -> Result<FileMetadata, IngestionError> {
+        Ok(FileMetadata::default())
+    }
+
+    async fn fetch_tags(&self, _bucket: &str, _key: &str) -> Result<HashMap<String, String>, IngestionError> {
+        Ok(HashMap::new())
+    }
+}
+
+#[async_trait]
+pub trait Decompressor: Send + Sync {
+    async fn open(&self, key: &str, stream: FileStream) -> Result<OpenedFile, IngestionError>;
 }
 
 #[async_trait]
 pub trait DataParser: Send + Sync {
     async fn parse(&self, file_bytes: &[u8], file_type: &str) -> Result<Vec<serde_json::Value>, IngestionError>;
     async fn parse_with_config(&self, file_bytes: &[u8], file_type: &str, config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError>;
+
+    fn supports_streaming(&self, _file_type: &str, _config: Option<&serde_json::Value>) -> bool {
+        false
+    }
+
+    /// File types this parser can handle, used to trust key extensions during detection.
+    fn file_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn file_type_for_content_type(&self, _content_type: &str) -> Option<String> {
+        None
+    }
+
+    async fn parse_stream(&self, _stream: FileStream, file_type: &str, _config: Option<&serde_json::Value>) -> Result<DocumentBatchStream, IngestionError> {
+        Err(IngestionError::Parse(format!("Streaming is not supported for file type: {}", file_type)))
+    }
 }
 
 #[async_trait]
 pub trait ConfigRepository: Send + Sync {
-    async fn get_config_for_key(&self, s3_key: &str) -> Result<Option<IngestionConfigRule>, IngestionError>;
+    /// Loads and validates every configuration rule, failing on the first invalid one.
+    async fn load_rules(&self) -> Result<Vec<IngestionConfigRule>, IngestionError>;
+    /// The best rule for `file`, matched on its key, bucket and `metadata`.
+    async fn get_config_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Option<IngestionConfigRule>, IngestionError>;
+
+    /// Every rule that applies to `file`, the best match first; empty when none match.
+    async fn get_configs_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        Ok(self.get_config_for_file(file, metadata).await?.into_iter().collect())
+    }
+
+    /// Whether any rule matches on object tags, so that they are worth fetching.
+    async fn uses_tags(&self) -> Result<bool, IngestionError> {
+        Ok(self.load_rules().await?.iter().any(|rule| !rule.matchers.tags.is_empty()))
+    }
 }
 
 #[async_trait]
 pub trait DataRepository: Send + Sync {
     async fn insert_documents(&self, target_table: &str, documents: &[serde_json::Value], log_id: &str) -> Result<Vec<String>, IngestionError>;
+
+    /// Deletes the documents stored under `log_id`, returning how many were deleted.
+    async fn delete_documents(&self, target_table: &str, log_id: &str) -> Result<u64, IngestionError>;
 }
 
 #[async_trait]
 pub trait LogRepository: Send + Sync {
     async fn insert_log(&self, log: &IngestionLog) -> Result<String, IngestionError>;
-    async fn update_log(&self, log_id: &str, end_time: DateTime<Utc>, status: IngestionStatus, message: Option<String>) -> Result<(), IngestionError>;
-}
\ No newline at end of file
+    async fn update_log(&self, log_id: &str, end_time: DateTime<Utc>, status: IngestionStatus, message: Option<String>, counts: DocumentCounts) -> Result<(), IngestionError>;
+
+    /// Earlier logs of `source` for `target_table` with their ids, oldest first.
+    async fn find_logs(&self, source: &SourceVersion, target_table: &str) -> Result<Vec<(String, IngestionLog)>, IngestionError>;
+}
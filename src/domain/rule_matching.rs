@@ -0,0 +1,212 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexSet};
+use tracing::{debug, warn, error};
+use crate::domain::{error::IngestionError, models::{FileMetadata, FileToProcess, IngestionConfigRule, RuleMatchers}};
+
+/// Which matching rules besides the best one apply to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FanoutMode {
+    /// Matching rules with `fanout: true`.
+    #[default]
+    Flagged,
+    /// Every matching rule.
+    All,
+}
+
+impl FromStr for FanoutMode {
+    type Err = IngestionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "flagged" => Ok(FanoutMode::Flagged),
+            "all" => Ok(FanoutMode::All),
+            other => Err(IngestionError::Config(format!("Unknown fan-out mode '{}', expected flagged or all", other))),
+        }
+    }
+}
+
+/// Picks the configuration rule for a key; shared by every `ConfigRepository`.
+///
+/// All patterns are compiled into one `RegexSet`. Among matching rules the highest
+/// `priority` wins; ties go to the longer (more specific) pattern, then to the
+/// lexicographically smaller pattern and target table, so the choice never depends on
+/// the order rules are stored in.
+///
+/// A rule's `matchers` are checked after its key pattern; a rule with a size or content
+/// type condition does not match when the source does not report that metadata.
+///
+/// Only one version of each `rule_id` is used at a time: the highest version whose
+/// validity period includes the current time. Disabled versions and versions outside
+/// their period never match.
+pub struct RuleMatcher {
+    rules: Vec<IngestionConfigRule>,
+    patterns: RegexSet,
+    conditions: Vec<Conditions>,
+    /// Indexes of the higher versions of each rule's `rule_id`.
+    newer_versions: Vec<Vec<usize>>,
+}
+
+/// A rule's `RuleMatchers` with the regexes compiled.
+struct Conditions {
+    bucket: Option<Regex>,
+    content_type: Option<Regex>,
+}
+
+impl Conditions {
+    fn compile(rule: &IngestionConfigRule) -> Result<Self, IngestionError> {
+        let compile = |field: &str, pattern: &Option<String>| pattern.as_deref()
+            .map(|pattern| Regex::new(pattern).map_err(|e| {
+                error!("Invalid {} matcher '{}' in rule '{}': {}", field, pattern, rule.pattern, e);
+                IngestionError::Config(format!("Rule '{}' (target '{}'): invalid regex in matchers.{}: {}", rule.pattern, rule.target_table, field, e))
+            }))
+            .transpose();
+        Ok(Self {
+            bucket: compile("bucket", &rule.matchers.bucket)?,
+            content_type: compile("content_type", &rule.matchers.content_type)?,
+        })
+    }
+
+    fn is_match(&self, matchers: &RuleMatchers, file: &FileToProcess, metadata: &FileMetadata) -> bool {
+        let regex_matches = |regex: &Option<Regex>, value: Option<&str>| match regex {
+            None => true,
+            Some(regex) => value.is_some_and(|value| regex.is_match(value)),
+        };
+        let size_in_range = match (matchers.min_size, matchers.max_size) {
+            (None, None) => true,
+            (min, max) => metadata.size.is_some_and(|size| min.is_none_or(|min| size >= min) && max.is_none_or(|max| size <= max)),
+        };
+
+        regex_matches(&self.bucket, Some(&file.bucket))
+            && regex_matches(&self.content_type, metadata.content_type.as_deref())
+            && size_in_range
+            && matchers.tags.iter().all(|(k, v)| metadata.tags.get(k) == Some(v))
+            // S3 lowercases user metadata keys, so rules may spell them either way
+            && matchers.metadata.iter().all(|(k, v)| metadata.user_metadata.iter().any(|(key, value)| key.eq_ignore_ascii_case(k) && value == v))
+    }
+}
+
+impl RuleMatcher {
+    pub fn new(rules: Vec<IngestionConfigRule>) -> Result<Self, IngestionError> {
+        let patterns = RegexSet::new(rules.iter().map(|rule| &rule.pattern)).map_err(|e| {
+            error!("Failed to compile rule patterns: {}", e);
+            IngestionError::Config(e.to_string())
+        })?;
+        let conditions = rules.iter().map(Conditions::compile).collect::<Result<Vec<_>, _>>()?;
+        let newer_versions = Self::index_versions(&rules)?;
+        Ok(Self { rules, patterns, conditions, newer_versions })
+    }
+
+    fn index_versions(rules: &[IngestionConfigRule]) -> Result<Vec<Vec<usize>>, IngestionError> {
+        let mut versions: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, rule) in rules.iter().enumerate() {
+            if let Some(id) = rule.rule_id.as_deref() {
+                versions.entry(id).or_default().push(i);
+            }
+        }
+
+        let mut newer_versions = vec![Vec::new(); rules.len()];
+        for (id, indexes) in versions {
+            for &i in &indexes {
+                for &j in &indexes {
+                    if i != j && rules[i].version == rules[j].version {
+                        error!("Rule '{}' has version {} more than once", id, rules[i].version);
+                        return Err(IngestionError::Config(format!("Rule '{}' has version {} more than once", id, rules[i].version)));
+                    }
+                    if rules[j].version > rules[i].version {
+                        newer_versions[i].push(j);
+                    }
+                }
+            }
+        }
+        Ok(newer_versions)
+    }
+
+    /// Whether rule `i` is enabled, in effect and not superseded by a newer version in effect.
+    fn is_active(&self, i: usize, now: DateTime<Utc>) -> bool {
+        let rule = &self.rules[i];
+        rule.enabled
+            && rule.is_in_effect(now)
+            && !self.newer_versions[i].iter().any(|&j| self.rules[j].is_in_effect(now))
+    }
+
+    pub fn rules(&self) -> &[IngestionConfigRule] {
+        &self.rules
+    }
+
+    /// Whether any rule has a `matchers.tags` condition.
+    pub fn uses_tags(&self) -> bool {
+        self.rules.iter().any(|rule| !rule.matchers.tags.is_empty())
+    }
+
+    /// Rules that match `file` and `metadata`, in precedence order.
+    pub fn matching(&self, file: &FileToProcess, metadata: &FileMetadata) -> Vec<&IngestionConfigRule> {
+        let now = Utc::now();
+        let mut matches: Vec<&IngestionConfigRule> = self.patterns.matches(&file.key).into_iter()
+            .filter(|&i| self.is_active(i, now))
+            .filter(|&i| {
+                let matched = self.conditions[i].is_match(&self.rules[i].matchers, file, metadata);
+                if !matched {
+                    debug!("❌ Rule '{}' matches key '{}' but not its bucket or metadata", self.rules[i].pattern, file.key);
+                }
+                matched
+            })
+            .map(|i| &self.rules[i])
+            .collect();
+        matches.sort_by(|a, b| precedence(a, b));
+        matches
+    }
+
+    /// The rule with the highest precedence for `file`.
+    ///
+    /// Logs a warning when another matching rule has the same priority, since the
+    /// choice between them then rests on the tie-breakers alone.
+    pub fn select(&self, file: &FileToProcess, metadata: &FileMetadata) -> Option<&IngestionConfigRule> {
+        self.select_targets(file, metadata, FanoutMode::Flagged).into_iter().next()
+    }
+
+    /// Every rule that applies to `file`: the best match first, followed by the other
+    /// matching rules that `mode` fans out to, in precedence order.
+    ///
+    /// A fan-out rule whose `target_table` is already targeted is dropped with a warning,
+    /// so that no file is stored twice in the same table.
+    pub fn select_targets(&self, file: &FileToProcess, metadata: &FileMetadata, mode: FanoutMode) -> Vec<&IngestionConfigRule> {
+        let key = file.key.as_str();
+        let matches = self.matching(file, metadata);
+        let Some((best, others)) = matches.split_first() else {
+            return Vec::new();
+        };
+
+        let mut targets = vec![*best];
+        let mut tied = Vec::new();
+        for rule in others {
+            if mode == FanoutMode::All || rule.fanout {
+                if let Some(first) = targets.iter().find(|target| target.target_table == rule.target_table) {
+                    warn!("Rules '{}' and '{}' both fan '{}' out to table '{}'; only '{}' applies",
+                        first.pattern, rule.pattern, key, rule.target_table, first.pattern);
+                    continue;
+                }
+                targets.push(*rule);
+            } else if rule.priority == best.priority {
+                tied.push(rule.pattern.as_str());
+            }
+        }
+        if !tied.is_empty() {
+            warn!("Rules {:?} match '{}' with the same priority {} as '{}'; using '{}'. Set distinct priorities to make this explicit",
+                tied, key, best.priority, best.pattern, best.pattern);
+        }
+
+        debug!("✅ Selected rule '{}' (priority {}) and {} fan-out rules for '{}' out of {} matches",
+            best.pattern, best.priority, targets.len() - 1, key, matches.len());
+        targets
+    }
+}
+
+fn precedence(a: &IngestionConfigRule, b: &IngestionConfigRule) -> Ordering {
+    b.priority.cmp(&a.priority)
+        .then_with(|| b.pattern.len().cmp(&a.pattern.len()))
+        .then_with(|| a.pattern.cmp(&b.pattern))
+        .then_with(|| a.target_table.cmp(&b.target_table))
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::domain::{dates::parse_date, error::IngestionError};
+
+/// One step of a rule's `transform` block, e.g. `{"rename": {"cust_nm": "customer_name"}}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum StepConfig {
+    /// Old field name to new name.
+    Rename(BTreeMap<String, String>),
+    Drop(Vec<String>),
+    /// Field to constant value.
+    Set(Map<String, Value>),
+    /// Field to a template such as `"{first_name} {last_name}"`.
+    Template(BTreeMap<String, String>),
+    Split(SplitStep),
+    Concat(ConcatStep),
+    ParseDate(ParseDateStep),
+    Nest(NestStep),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SplitStep {
+    field: String,
+    separator: String,
+    /// Defaults to replacing `field`.
+    #[serde(default)]
+    into: Option<String>,
+    #[serde(default)]
+    trim: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConcatStep {
+    fields: Vec<String>,
+    #[serde(default)]
+    separator: String,
+    into: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParseDateStep {
+    field: String,
+    /// chrono format; RFC 3339 and ISO 8601 dates are recognised without one.
+    #[serde(default)]
+    format: Option<String>,
+    /// Defaults to replacing `field`.
+    #[serde(default)]
+    into: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NestStep {
+    #[serde(default = "default_nest_separator")]
+    separator: String,
+}
+
+fn default_nest_separator() -> String {
+    ".".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    Field(String),
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Rename(BTreeMap<String, String>),
+    Drop(Vec<String>),
+    Set(Map<String, Value>),
+    Template(Vec<(String, Vec<TemplatePart>)>),
+    Split(SplitStep),
+    Concat(ConcatStep),
+    ParseDate(ParseDateStep),
+    Nest(NestStep),
+}
+
+/// The declarative field mapping of a rule, applied to every parsed document before it
+/// is stored.
+///
+/// Steps run in order, so a later step sees the fields produced by earlier ones. Missing
+/// or null source fields are skipped, except in templates where they render as empty text.
+#[derive(Debug, Clone, Default)]
+pub struct Transform {
+    steps: Vec<Step>,
+}
+
+impl Transform {
+    /// Compiles a rule's `transform` block: an array of single-key step objects.
+    pub fn from_config(config: Option<&Value>) -> Result<Self, IngestionError> {
+        let steps = match config {
+            None | Some(Value::Null) => return Ok(Self::default()),
+            Some(Value::Array(steps)) => steps,
+            Some(_) => return Err(IngestionError::Config("transform must be an array of steps".to_string())),
+        };
+
+        let steps = steps.iter().enumerate()
+            .map(|(i, step)| {
+                let step: StepConfig = serde_json::from_value(step.clone())
+                    .map_err(|e| IngestionError::Config(format!("transform[{}]: {}", i, e)))?;
+                Ok(match step {
+                    StepConfig::Rename(fields) => Step::Rename(fields),
+                    StepConfig::Drop(fields) => Step::Drop(fields),
+                    StepConfig::Set(fields) => Step::Set(fields),
+                    StepConfig::Template(templates) => Step::Template(templates.into_iter()
+                        .map(|(field, template)| compile_template(&template)
+                            .map(|parts| (field.clone(), parts))
+                            .map_err(|e| IngestionError::Config(format!("transform[{}].template.{}: {}", i, field, e))))
+                        .collect::<Result<_, _>>()?),
+                    StepConfig::Split(split) if split.separator.is_empty() => {
+                        return Err(IngestionError::Config(format!("transform[{}].split: separator must not be empty", i)));
+                    },
+                    StepConfig::Split(split) => Step::Split(split),
+                    StepConfig::Concat(concat) => Step::Concat(concat),
+                    StepConfig::ParseDate(parse) => Step::ParseDate(parse),
+                    StepConfig::Nest(nest) if nest.separator.is_empty() => {
+                        return Err(IngestionError::Config(format!("transform[{}].nest: separator must not be empty", i)));
+                    },
+                    StepConfig::Nest(nest) => Step::Nest(nest),
+                })
+            })
+            .collect::<Result<Vec<_>, IngestionError>>()?;
+        Ok(Self { steps })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Applies every step to `document`, which must be a JSON object.
+    pub fn apply(&self, document: Value) -> Result<Value, String> {
+        let Value::Object(mut fields) = document else {
+            return if self.is_empty() { Ok(document) } else { Err("document is not an object".to_string()) };
+        };
+
+        for step in &self.steps {
+            match step {
+                Step::Rename(renames) => {
+                    // Take all old fields first so that renames such as a -> b, b -> a swap
+                    let moved: Vec<(&String, Value)> = renames.iter()
+                        .filter_map(|(from, to)| fields.remove(from).map(|value| (to, value)))
+                        .collect();
+                    for (to, value) in moved {
+                        fields.insert(to.clone(), value);
+                    }
+                },
+                Step::Drop(names) => {
+                    for name in names {
+                        fields.remove(name);
+                    }
+                },
+                Step::Set(constants) => {
+                    for (name, value) in constants {
+                        fields.insert(name.clone(), value.clone());
+                    }
+                },
+                Step::Template(templates) => {
+                    let rendered: Vec<(String, String)> = templates.iter()
+                        .map(|(name, parts)| (name.clone(), render_template(parts, &fields)))
+                        .collect();
+                    for (name, value) in rendered {
+                        fields.insert(name, Value::String(value));
+                    }
+                },
+                Step::Split(split) => {
+                    let Some(value) = fields.get(&split.field).filter(|v| !v.is_null()) else { continue };
+                    let text = value.as_str()
+                        .ok_or_else(|| format!("cannot split field '{}': not a string", split.field))?;
+                    let parts: Vec<Value> = text.split(split.separator.as_str())
+                        .map(|part| Value::String(if split.trim { part.trim() } else { part }.to_string()))
+                        .collect();
+                    fields.insert(split.into.clone().unwrap_or_else(|| split.field.clone()), Value::Array(parts));
+                },
+                Step::Concat(concat) => {
+                    let parts: Vec<String> = concat.fields.iter()
+                        .filter_map(|name| fields.get(name).filter(|v| !v.is_null()).map(text_of))
+                        .collect();
+                    fields.insert(concat.into.clone(), Value::String(parts.join(&concat.separator)));
+                },
+                Step::ParseDate(parse) => {
+                    let Some(value) = fields.get(&parse.field).filter(|v| !v.is_null()) else { continue };
+                    let raw = text_of(value);
+                    let date = parse_date(raw.trim(), parse.format.as_deref()).ok_or_else(|| match &parse.format {
+                        Some(format) => format!("cannot parse field '{}' value '{}' as a date with format '{}'", parse.field, raw, format),
+                        None => format!("cannot parse field '{}' value '{}' as a date", parse.field, raw),
+                    })?;
+                    fields.insert(parse.into.clone().unwrap_or_else(|| parse.field.clone()), Value::String(date));
+                },
+                Step::Nest(nest) => fields = nest_fields(fields, &nest.separator)?,
+            }
+        }
+
+        Ok(Value::Object(fields))
+    }
+}
+
+/// Splits `"Hello {name}"` into literals and field references; `{{` and `}}` are literal braces.
+fn compile_template(template: &str) -> Result<Vec<TemplatePart>, String> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            },
+            '{' => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') | None => return Err(format!("unclosed placeholder in '{}'", template)),
+                        Some(c) => field.push(c),
+                    }
+                }
+                if field.trim().is_empty() {
+                    return Err(format!("empty placeholder in '{}'", template));
+                }
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(TemplatePart::Field(field));
+            },
+            '}' => return Err(format!("unmatched '}}' in '{}'", template)),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+    Ok(parts)
+}
+
+fn render_template(parts: &[TemplatePart], fields: &Map<String, Value>) -> String {
+    parts.iter()
+        .map(|part| match part {
+            TemplatePart::Literal(text) => text.clone(),
+            TemplatePart::Field(name) => fields.get(name).filter(|v| !v.is_null()).map(text_of).unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Strings as they are, other values as JSON.
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Moves `address.city` style fields into sub-objects.
+fn nest_fields(fields: Map<String, Value>, separator: &str) -> Result<Map<String, Value>, String> {
+    let mut nested = Map::new();
+    for (name, value) in fields {
+        if !name.contains(separator) {
+            match (nested.get_mut(&name), value) {
+                (Some(Value::Object(existing)), Value::Object(value)) => existing.extend(value),
+                (Some(_), _) => return Err(format!("field '{}' conflicts with nested fields of the same name", name)),
+                (None, value) => { nested.insert(name, value); },
+            }
+            continue;
+        }
+
+        let path: Vec<&str> = name.split(separator).collect();
+        let (leaf, parents) = path.split_last().expect("split yields at least one part");
+        let mut target = &mut nested;
+        for parent in parents {
+            let entry = target.entry(parent.to_string()).or_insert_with(|| Value::Object(Map::new()));
+            target = match entry {
+                Value::Object(object) => object,
+                _ => return Err(format!("cannot nest '{}': '{}' is not an object", name, parent)),
+            };
+        }
+        if target.contains_key(*leaf) {
+            return Err(format!("cannot nest '{}': '{}' is already set", name, leaf));
+        }
+        target.insert(leaf.to_string(), value);
+    }
+    Ok(nested)
+}
@@ -1,13 +1,18 @@
 use std::sync::Arc;
+use std::time::Duration;
 use aws_sdk_sqs::Client as SqsClient;
 use serde_json::Value;
 use tracing::{info, error, debug, warn};
 use crate::{
-    application::ingestion_service::IngestionService,
-    domain::models::FileToProcess,
+    application::ingestion_service::{IngestionService, DEFAULT_STALE_LOG_TIMEOUT},
+    domain::{error::IngestionError, models::FileToProcess, ports::ConfigRepository, rule_matching::FanoutMode},
     infrastructure::{
         s3_adapter::S3Adapter,
         parser_adapter::ParserAdapter,
+        parsers::registry::ParserRegistry,
+        rule_cache::DEFAULT_RULE_CACHE_TTL,
+        rule_validator::RuleValidator,
+        decompression_adapter::{DecompressionAdapter, DEFAULT_MAX_ARCHIVE_BYTES, DEFAULT_MAX_ENTRY_BYTES},
         mongodb::{config_repo::MongoConfigRepository, data_repo::MongoDataRepository, log_repo::MongoLogRepository},
         documentdb::{config_repo::DocumentDBConfigRepository, data_repo::DocumentDBDataRepository},
     },
@@ -21,6 +26,12 @@ pub struct EcsService {
 
 impl EcsService {
     pub async fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::with_parsers(ParserRegistry::with_builtin_parsers()).await
+    }
+
+    /// Builds the service with a custom parser registry, e.g. the built-in parsers
+    /// plus parsers registered by another crate.
+    pub async fn with_parsers(registry: ParserRegistry) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         debug!("Initializing ECS service");
         
         debug!("Loading AWS configuration");
@@ -51,8 +62,35 @@ impl EcsService {
         info!("Using SQS queue: {}", queue_url);
         
         let file_fetcher = Arc::new(S3Adapter::new(s3_client));
-        let parser = Arc::new(ParserAdapter::new());
-        debug!("S3 adapter and parser initialized");
+        let max_entry_bytes = std::env::var("ARCHIVE_MAX_ENTRY_BYTES").ok()
+            .and_then(|bytes| bytes.parse().ok())
+            .unwrap_or(DEFAULT_MAX_ENTRY_BYTES);
+        let max_archive_bytes = std::env::var("ARCHIVE_MAX_TOTAL_BYTES").ok()
+            .and_then(|bytes| bytes.parse().ok())
+            .unwrap_or(DEFAULT_MAX_ARCHIVE_BYTES);
+        info!("Archive limits: {} bytes per entry, {} bytes per archive", max_entry_bytes, max_archive_bytes);
+        let decompressor = Arc::new(DecompressionAdapter::new().with_archive_limits(max_entry_bytes, max_archive_bytes));
+        for parser in registry.parsers() {
+            info!("Registered parser '{}' for types {:?}, MIME types {:?}", parser.name(), parser.file_types(), parser.mime_types());
+        }
+        let validator = RuleValidator::new(registry.clone());
+        let rule_cache_ttl = std::env::var("CONFIG_CACHE_TTL_SECONDS").ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_RULE_CACHE_TTL);
+        info!("Caching config rules for {:?}", rule_cache_ttl);
+        let fanout_mode: FanoutMode = std::env::var("RULE_FANOUT").ok()
+            .map(|mode| mode.parse())
+            .transpose()?
+            .unwrap_or_default();
+        info!("Rule fan-out mode: {:?}", fanout_mode);
+        let stale_log_timeout = std::env::var("STALE_LOG_TIMEOUT_SECONDS").ok()
+            .and_then(|timeout| timeout.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_STALE_LOG_TIMEOUT);
+        info!("Treating logs in progress for over {:?} as abandoned", stale_log_timeout);
+        let parser = Arc::new(ParserAdapter::with_registry(registry));
+        debug!("S3 adapter, decompressor and parser initialized");
         
         let db_type = std::env::var("DATABASE_TYPE").unwrap_or_else(|_| "mongodb".to_string());
         info!("Using database type: {}", db_type);
@@ -74,12 +112,19 @@ impl EcsService {
                         e
                     })?;
                 
-                let config_repo = Arc::new(DocumentDBConfigRepository::new(documentdb_client.clone(), documentdb_database.clone(), config_collection));
+                let config_repo = Arc::new(DocumentDBConfigRepository::new(documentdb_client.clone(), documentdb_database.clone(), config_collection)
+                    .with_validator(validator)
+                    .with_fanout_mode(fanout_mode)
+                    .with_cache_ttl(rule_cache_ttl));
+                Self::check_rules(config_repo.as_ref()).await?;
+                config_repo.spawn_change_listener();
                 let data_repo = Arc::new(DocumentDBDataRepository::new(documentdb_client.clone(), documentdb_database.clone()));
                 let log_repo = Arc::new(MongoLogRepository::new(documentdb_client, documentdb_database));
+                log_repo.create_indexes().await?;
                 debug!("DocumentDB repositories initialized");
                 
-                IngestionService::new(file_fetcher, parser, config_repo, data_repo, log_repo)
+                IngestionService::new(file_fetcher, decompressor, parser, config_repo, data_repo, log_repo)
+                    .with_stale_log_timeout(stale_log_timeout)
             },
             _ => {
                 debug!("Initializing MongoDB repositories");
@@ -97,12 +142,19 @@ impl EcsService {
                     })?;
                 debug!("MongoDB client connected successfully");
                 
-                let config_repo = Arc::new(MongoConfigRepository::new(&mongo_client, &mongo_db));
+                let config_repo = Arc::new(MongoConfigRepository::new(&mongo_client, &mongo_db)
+                    .with_validator(validator)
+                    .with_fanout_mode(fanout_mode)
+                    .with_cache_ttl(rule_cache_ttl));
+                Self::check_rules(config_repo.as_ref()).await?;
+                config_repo.spawn_change_listener();
                 let data_repo = Arc::new(MongoDataRepository::new(mongo_client.clone(), mongo_db.clone()));
                 let log_repo = Arc::new(MongoLogRepository::new(mongo_client, mongo_db));
+                log_repo.create_indexes().await?;
                 debug!("MongoDB repositories initialized");
                 
-                IngestionService::new(file_fetcher, parser, config_repo, data_repo, log_repo)
+                IngestionService::new(file_fetcher, decompressor, parser, config_repo, data_repo, log_repo)
+                    .with_stale_log_timeout(stale_log_timeout)
             }
         };
         
@@ -110,6 +162,16 @@ impl EcsService {
         Ok(Self { service, sqs_client, queue_url })
     }
 
+    /// Refuses to start when any configuration rule is invalid.
+    async fn check_rules(config_repo: &dyn ConfigRepository) -> Result<(), IngestionError> {
+        let rules = config_repo.load_rules().await.map_err(|e| {
+            error!("Configuration rules failed validation: {}", e);
+            e
+        })?;
+        info!("✅ Validated {} configuration rules", rules.len());
+        Ok(())
+    }
+
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Starting ECS service, polling SQS queue: {}", self.queue_url);
         
@@ -1,17 +1,22 @@
+use std::time::Duration;
 use async_trait::async_trait;
 use reqwest::Client;
-use regex::Regex;
 use serde_json::Value;
 use crate::domain::{
     error::IngestionError,
-    models::IngestionConfigRule,
+    models::{FileMetadata, FileToProcess, IngestionConfigRule},
     ports::ConfigRepository,
+    rule_matching::FanoutMode,
 };
+use crate::infrastructure::{rule_cache::RuleCache, rule_validator::RuleValidator};
 
 pub struct CouchConfigRepository {
     client: Client,
     base_url: String,
     database: String,
+    validator: RuleValidator,
+    fanout: FanoutMode,
+    cache: RuleCache,
 }
 
 impl CouchConfigRepository {
@@ -20,13 +25,55 @@ impl CouchConfigRepository {
             client: Client::new(),
             base_url,
             database,
+            validator: RuleValidator::default(),
+            fanout: FanoutMode::default(),
+            cache: RuleCache::default(),
         }
     }
+
+    /// Validates rules against `validator` instead of the built-in parsers.
+    pub fn with_validator(mut self, validator: RuleValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Applies every matching rule instead of only those flagged `fanout` when set to `FanoutMode::All`.
+    pub fn with_fanout_mode(mut self, fanout: FanoutMode) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    /// Reloads cached rules after `ttl` instead of the default.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache = RuleCache::new(ttl);
+        self
+    }
+
+    /// Reads and validates the rules of an `_all_docs?include_docs=true` response, skipping
+    /// design documents, which share the database but are not rules.
+    pub(crate) fn rules_from_all_docs(&self, result: &Value) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        let mut rules = Vec::new();
+        if let Some(rows) = result["rows"].as_array() {
+            for row in rows {
+                if row["id"].as_str().is_some_and(|id| id.starts_with("_design/")) {
+                    continue;
+                }
+                if let Some(doc) = row["doc"].as_object() {
+                    let rule: IngestionConfigRule = serde_json::from_value(Value::Object(doc.clone()))
+                        .map_err(|e| IngestionError::Database(e.to_string()))?;
+                    self.validator.validate(&rule)?;
+                    rules.push(rule);
+                }
+            }
+        }
+        
+        Ok(rules)
+    }
 }
 
 #[async_trait]
 impl ConfigRepository for CouchConfigRepository {
-    async fn get_config_for_key(&self, s3_key: &str) -> Result<Option<IngestionConfigRule>, IngestionError> {
+    async fn load_rules(&self) -> Result<Vec<IngestionConfigRule>, IngestionError> {
         let url = format!("{}/{}/_all_docs?include_docs=true", self.base_url, self.database);
         
         let response = self.client
@@ -40,22 +87,19 @@ impl ConfigRepository for CouchConfigRepository {
             .await
             .map_err(|e| IngestionError::Database(e.to_string()))?;
 
-        if let Some(rows) = result["rows"].as_array() {
-            for row in rows {
-                if let Some(doc) = row["doc"].as_object() {
-                    let rule: IngestionConfigRule = serde_json::from_value(Value::Object(doc.clone()))
-                        .map_err(|e| IngestionError::Database(e.to_string()))?;
-                    
-                    let regex = Regex::new(&rule.pattern)
-                        .map_err(|e| IngestionError::Config(e.to_string()))?;
-                    
-                    if regex.is_match(s3_key) {
-                        return Ok(Some(rule));
-                    }
-                }
-            }
-        }
-        
-        Ok(None)
+        self.rules_from_all_docs(&result)
+    }
+
+    async fn get_config_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Option<IngestionConfigRule>, IngestionError> {
+        let rules = self.cache.get_or_load(|| self.load_rules()).await?;
+        Ok(rules.select(file, metadata).cloned())
+    }
+
+    async fn get_configs_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        let rules = self.cache.get_or_load(|| self.load_rules()).await?;
+        Ok(rules.select_targets(file, metadata, self.fanout).into_iter().cloned().collect())
+    }
+    async fn uses_tags(&self) -> Result<bool, IngestionError> {
+        Ok(self.cache.get_or_load(|| self.load_rules()).await?.uses_tags())
     }
 }
\ No newline at end of file
@@ -5,15 +5,13 @@ use crate::domain::{error::IngestionError, ports::DataRepository};
 pub struct CouchDataRepository {
     client: Client,
     base_url: String,
-    database: String,
 }
 
 impl CouchDataRepository {
-    pub fn new(base_url: String, database: String) -> Self {
+    pub fn new(base_url: String) -> Self {
         Self {
             client: Client::new(),
             base_url,
-            database,
         }
     }
 }
@@ -57,4 +55,55 @@ impl DataRepository for CouchDataRepository {
 
         Ok(ids)
     }
-}
\ No newline at end of file
+
+    async fn delete_documents(&self, target_table: &str, log_id: &str) -> Result<u64, IngestionError> {
+        let find_url = format!("{}/{}/_find", self.base_url, target_table);
+        let bulk_url = format!("{}/{}/_bulk_docs", self.base_url, target_table);
+        let mut deleted = 0;
+        
+        // Mango queries return a page at a time, so delete until nothing matches
+        loop {
+            let query = serde_json::json!({
+                "selector": { "log_id": log_id },
+                "fields": ["_id", "_rev"],
+                "limit": 1000
+            });
+            let response = self.client
+                .post(&find_url)
+                .json(&query)
+                .send()
+                .await
+                .map_err(|e| IngestionError::Database(e.to_string()))?;
+            let result: serde_json::Value = response.json().await
+                .map_err(|e| IngestionError::Database(e.to_string()))?;
+            
+            let tombstones: Vec<serde_json::Value> = result["docs"].as_array()
+                .unwrap_or(&vec![])
+                .iter()
+                .map(|doc| serde_json::json!({ "_id": doc["_id"], "_rev": doc["_rev"], "_deleted": true }))
+                .collect();
+            if tombstones.is_empty() {
+                return Ok(deleted);
+            }
+            
+            let response = self.client
+                .post(&bulk_url)
+                .json(&serde_json::json!({ "docs": tombstones }))
+                .send()
+                .await
+                .map_err(|e| IngestionError::Database(e.to_string()))?;
+            let result: serde_json::Value = response.json().await
+                .map_err(|e| IngestionError::Database(e.to_string()))?;
+            
+            let deleted_now = result.as_array()
+                .unwrap_or(&vec![])
+                .iter()
+                .filter(|item| item["ok"].as_bool() == Some(true))
+                .count() as u64;
+            if deleted_now == 0 {
+                return Err(IngestionError::Database(format!("Failed to delete documents of log {} from {}: {}", log_id, target_table, result)));
+            }
+            deleted += deleted_now;
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::io::Read;
+use std::path::Path;
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, ZstdDecoder};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio_util::io::StreamReader;
+use tracing::{debug, info, warn, error};
+use crate::domain::{
+    error::IngestionError,
+    ports::{ArchiveEntries, ArchiveEntry, Decompressor, FileStream, OpenedFile},
+};
+use crate::infrastructure::spool::SpoolFile;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Zip,
+}
+
+impl Compression {
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "gz" | "gzip" => Some(Self::Gzip),
+            "zst" | "zstd" => Some(Self::Zstd),
+            "bz2" => Some(Self::Bzip2),
+            "zip" => Some(Self::Zip),
+            _ => None,
+        }
+    }
+
+    fn from_magic(head: &[u8]) -> Option<Self> {
+        if head.starts_with(GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if head.starts_with(ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else if head.starts_with(BZIP2_MAGIC) {
+            Some(Self::Bzip2)
+        } else if head.starts_with(ZIP_MAGIC) {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Default cap on the uncompressed size of a single archive entry (1 GiB).
+pub const DEFAULT_MAX_ENTRY_BYTES: u64 = 1 << 30;
+/// Default cap on the uncompressed size of all entries of an archive together (4 GiB).
+pub const DEFAULT_MAX_ARCHIVE_BYTES: u64 = 4 << 30;
+
+/// Bytes handed over per read of an archive entry.
+const ENTRY_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Detects compressed objects by extension and magic bytes and unwraps them.
+pub struct DecompressionAdapter {
+    max_entry_bytes: u64,
+    max_archive_bytes: u64,
+}
+
+impl DecompressionAdapter {
+    pub fn new() -> Self {
+        debug!("Initializing decompression adapter");
+        Self { max_entry_bytes: DEFAULT_MAX_ENTRY_BYTES, max_archive_bytes: DEFAULT_MAX_ARCHIVE_BYTES }
+    }
+
+    /// Fails archive entries that decompress to more than `max_entry_bytes`, and stops
+    /// reading an archive once its entries add up to more than `max_archive_bytes`.
+    pub fn with_archive_limits(mut self, max_entry_bytes: u64, max_archive_bytes: u64) -> Self {
+        self.max_entry_bytes = max_entry_bytes;
+        self.max_archive_bytes = max_archive_bytes;
+        self
+    }
+}
+
+impl Default for DecompressionAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Picks the codec for `key`, returning the key with the compression suffix removed.
+fn detect(key: &str, head: &[u8]) -> (Option<Compression>, String) {
+    let file_name = key.rsplit('/').next().unwrap_or(key);
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+
+    if let Some(codec) = extension.as_deref().and_then(Compression::from_extension) {
+        let inner = &key[..key.len() - extension.as_deref().map(|e| e.len() + 1).unwrap_or(0)];
+        return (Some(codec), inner.to_string());
+    }
+
+    // Zip containers are also used by xlsx and ods, so only sniff archives without an extension
+    match Compression::from_magic(head) {
+        Some(Compression::Zip) if extension.is_some() => (None, key.to_string()),
+        codec => (codec, key.to_string()),
+    }
+}
+
+/// Extracts the entries of the zip archive spooled at `spool` on the blocking thread pool,
+/// handing each one over as a stream once the previous one has been consumed.
+fn stream_zip_entries(spool: SpoolFile, max_entry_bytes: u64, max_archive_bytes: u64) -> ArchiveEntries {
+    let (tx, mut rx) = mpsc::channel::<Result<ArchiveEntry, IngestionError>>(1);
+
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = extract_zip(spool.path(), max_entry_bytes, max_archive_bytes, &tx) {
+            let _ = tx.blocking_send(Err(e));
+        }
+        // The spool file is removed once every entry has been read
+        drop(spool);
+    });
+
+    Box::pin(futures_util::stream::poll_fn(move |cx| rx.poll_recv(cx)))
+}
+
+fn extract_zip(
+    path: &Path,
+    max_entry_bytes: u64,
+    max_archive_bytes: u64,
+    entries: &mpsc::Sender<Result<ArchiveEntry, IngestionError>>,
+) -> Result<(), IngestionError> {
+    let file = std::fs::File::open(path).map_err(|e| {
+        error!("Failed to open spooled zip archive {}: {}", path.display(), e);
+        IngestionError::Decompression(e.to_string())
+    })?;
+    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file)).map_err(|e| {
+        error!("Failed to open zip archive: {}", e);
+        IngestionError::Decompression(e.to_string())
+    })?;
+    info!("Zip archive has {} entries", archive.len());
+
+    let mut archive_bytes = 0u64;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| {
+            error!("Failed to read zip entry {}: {}", i, e);
+            IngestionError::Decompression(e.to_string())
+        })?;
+        let name = file.name().to_string();
+        if file.is_dir() || name.starts_with("__MACOSX/") {
+            debug!("Skipping zip entry: {}", name);
+            continue;
+        }
+
+        if file.size() > max_entry_bytes {
+            error!("Zip entry {} declares {} bytes, more than the limit of {}", name, file.size(), max_entry_bytes);
+            let error = IngestionError::Archive(format!("{}: {} bytes uncompressed, more than the limit of {}", name, file.size(), max_entry_bytes));
+            if entries.blocking_send(Err(error)).is_err() {
+                return Ok(());
+            }
+            continue;
+        }
+
+        let (chunks, mut content) = mpsc::channel::<std::io::Result<Bytes>>(4);
+        // Readers may poll again after the end, which a channel receiver tolerates
+        let stream: FileStream = Box::pin(StreamReader::new(futures_util::stream::poll_fn(move |cx| content.poll_recv(cx))));
+        if entries.blocking_send(Ok(ArchiveEntry { name: name.clone(), size: file.size(), stream })).is_err() {
+            debug!("Archive consumer was dropped before entry {}", name);
+            return Ok(());
+        }
+
+        // The declared size may lie, so the limits are enforced on what is actually inflated
+        let mut entry_bytes = 0u64;
+        let mut buffer = vec![0; ENTRY_CHUNK_BYTES];
+        loop {
+            let read = match file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    error!("Failed to decompress zip entry {}: {}", name, e);
+                    let _ = chunks.blocking_send(Err(std::io::Error::new(e.kind(), format!("{}: {}", name, e))));
+                    break;
+                },
+            };
+            entry_bytes += read as u64;
+            archive_bytes += read as u64;
+
+            if archive_bytes > max_archive_bytes {
+                error!("Zip archive inflates to more than the limit of {} bytes, stopping at entry {}", max_archive_bytes, name);
+                let message = format!("archive inflates to more than the limit of {} bytes", max_archive_bytes);
+                let _ = chunks.blocking_send(Err(std::io::Error::other(message.clone())));
+                return Err(IngestionError::Archive(format!("{}, entries from {} on were not ingested", message, name)));
+            }
+            if entry_bytes > max_entry_bytes {
+                error!("Zip entry {} inflates to more than the limit of {} bytes", name, max_entry_bytes);
+                let _ = chunks.blocking_send(Err(std::io::Error::other(format!("{} inflates to more than the limit of {} bytes", name, max_entry_bytes))));
+                break;
+            }
+            if chunks.blocking_send(Ok(Bytes::copy_from_slice(&buffer[..read]))).is_err() {
+                debug!("Zip entry {} was not read to the end", name);
+                break;
+            }
+        }
+        debug!("Extracted zip entry {} ({} bytes)", name, entry_bytes);
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl Decompressor for DecompressionAdapter {
+    async fn open(&self, key: &str, stream: FileStream) -> Result<OpenedFile, IngestionError> {
+        let mut reader = BufReader::new(stream);
+        let head = reader.fill_buf().await
+            .map_err(|e| {
+                error!("Failed to read start of {}: {}", key, e);
+                IngestionError::S3(e.to_string())
+            })?
+            .to_vec();
+
+        let (codec, inner_key) = detect(key, &head);
+        let Some(codec) = codec else {
+            debug!("No compression detected for {}", key);
+            return Ok(OpenedFile::Single { key: key.to_string(), stream: Box::pin(reader) });
+        };
+
+        if inner_key == key {
+            warn!("Detected {:?} content in {} from magic bytes", codec, key);
+        }
+        info!("Decompressing {} as {:?}, dispatching as {}", key, codec, inner_key);
+
+        let stream: FileStream = match codec {
+            Compression::Gzip => {
+                let mut decoder = GzipDecoder::new(reader);
+                decoder.multiple_members(true);
+                Box::pin(decoder)
+            },
+            Compression::Zstd => Box::pin(ZstdDecoder::new(reader)),
+            Compression::Bzip2 => Box::pin(BzDecoder::new(reader)),
+            Compression::Zip => {
+                // The zip central directory sits at the end, so archives are spooled to disk
+                let spool = SpoolFile::new("zip");
+                let spool_error = |e: std::io::Error| {
+                    error!("Failed to spool zip archive {} to {}: {}", key, spool.path().display(), e);
+                    IngestionError::Decompression(e.to_string())
+                };
+                let mut file = tokio::fs::OpenOptions::new().write(true).create_new(true).open(spool.path()).await
+                    .map_err(spool_error)?;
+                let spooled = tokio::io::copy(&mut reader, &mut file).await.map_err(spool_error)?;
+                file.flush().await.map_err(spool_error)?;
+                drop(file);
+                info!("Spooled zip archive {} ({} bytes), extracting its entries", key, spooled);
+                return Ok(OpenedFile::Archive(stream_zip_entries(spool, self.max_entry_bytes, self.max_archive_bytes)));
+            },
+        };
+
+        Ok(OpenedFile::Single { key: inner_key, stream })
+    }
+}
@@ -1,56 +1,104 @@
+use std::sync::Arc;
+use std::time::Duration;
 use async_trait::async_trait;
-use mongodb::{Client, Collection, bson::doc};
+use mongodb::{Client, Collection, bson::{doc, Bson}};
 use futures_util::TryStreamExt;
-use regex::Regex;
+use serde_json::Value;
+use tokio::task::JoinHandle;
 use crate::domain::{
     error::IngestionError,
-    models::IngestionConfigRule,
+    models::{FileMetadata, FileToProcess, IngestionConfigRule},
     ports::ConfigRepository,
+    rule_matching::FanoutMode,
 };
+use crate::infrastructure::mongodb::config_repo::watch_rule_changes;
+use crate::infrastructure::{rule_cache::RuleCache, rule_validator::RuleValidator};
 
 pub struct DocumentDBConfigRepository {
     client: Client,
     database_name: String,
     collection_name: String,
+    validator: RuleValidator,
+    fanout: FanoutMode,
+    cache: Arc<RuleCache>,
 }
 
 impl DocumentDBConfigRepository {
     pub fn new(client: Client, database_name: String, collection_name: String) -> Self {
-        Self { client, database_name, collection_name }
+        Self { client, database_name, collection_name, validator: RuleValidator::default(), fanout: FanoutMode::default(), cache: Arc::new(RuleCache::default()) }
+    }
+
+    /// Validates rules against `validator` instead of the built-in parsers.
+    pub fn with_validator(mut self, validator: RuleValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Applies every matching rule instead of only those flagged `fanout` when set to `FanoutMode::All`.
+    pub fn with_fanout_mode(mut self, fanout: FanoutMode) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    /// Reloads cached rules after `ttl` instead of the default.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache = Arc::new(RuleCache::new(ttl));
+        self
+    }
+
+    /// Invalidates the rule cache whenever the config collection changes.
+    pub fn spawn_change_listener(&self) -> JoinHandle<()> {
+        let collection = self.client.database(&self.database_name).collection(&self.collection_name);
+        watch_rule_changes(collection, self.cache.clone())
     }
 }
 
 #[async_trait]
 impl ConfigRepository for DocumentDBConfigRepository {
-    async fn get_config_for_key(&self, s3_key: &str) -> Result<Option<IngestionConfigRule>, IngestionError> {
+    async fn load_rules(&self) -> Result<Vec<IngestionConfigRule>, IngestionError> {
         let db = self.client.database(&self.database_name);
         let collection: Collection<mongodb::bson::Document> = db.collection(&self.collection_name);
         
         let mut cursor = collection.find(doc! {}, None).await
             .map_err(|e| IngestionError::Database(e.to_string()))?;
         
+        let mut rules = Vec::new();
         while let Some(item) = cursor.try_next().await
             .map_err(|e| IngestionError::Database(e.to_string()))? {
             if let (Some(pattern), Some(target_table)) = (
                 item.get_str("pattern").ok(),
                 item.get_str("target_table").ok(),
             ) {
-                let regex = Regex::new(pattern)
-                    .map_err(|e| IngestionError::Config(e.to_string()))?;
+                // parser_config may be stored as a JSON string or as an embedded document
+                let parser_config = match item.get("parser_config") {
+                    Some(Bson::String(s)) => Some(serde_json::from_str(s)
+                        .map_err(|e| IngestionError::Config(format!("Rule '{}' (target '{}'): parser_config is not valid JSON: {}", pattern, target_table, e)))?),
+                    Some(Bson::Null) | None => None,
+                    Some(other) => Some(other.clone().into_relaxed_extjson()),
+                };
                 
-                if regex.is_match(s3_key) {
-                    let parser_config = item.get_str("parser_config").ok()
-                        .and_then(|s| serde_json::from_str(s).ok());
-                    
-                    return Ok(Some(IngestionConfigRule {
-                        pattern: pattern.to_string(),
-                        target_table: target_table.to_string(),
-                        parser_config,
-                    }));
-                }
+                let mut fields = Bson::Document(item.clone()).into_relaxed_extjson();
+                fields["parser_config"] = parser_config.unwrap_or(Value::Null);
+                let rule: IngestionConfigRule = serde_json::from_value(fields)
+                    .map_err(|e| IngestionError::Config(format!("Rule '{}' (target '{}'): {}", pattern, target_table, e)))?;
+                self.validator.validate(&rule)?;
+                rules.push(rule);
             }
         }
         
-        Ok(None)
+        Ok(rules)
+    }
+
+    async fn get_config_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Option<IngestionConfigRule>, IngestionError> {
+        let rules = self.cache.get_or_load(|| self.load_rules()).await?;
+        Ok(rules.select(file, metadata).cloned())
+    }
+
+    async fn get_configs_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        let rules = self.cache.get_or_load(|| self.load_rules()).await?;
+        Ok(rules.select_targets(file, metadata, self.fanout).into_iter().cloned().collect())
+    }
+    async fn uses_tags(&self) -> Result<bool, IngestionError> {
+        Ok(self.cache.get_or_load(|| self.load_rules()).await?.uses_tags())
     }
 }
\ No newline at end of file
@@ -1,16 +1,32 @@
 use async_trait::async_trait;
-use mongodb::{Client, Collection, bson::doc};
-use uuid::Uuid;
+use mongodb::{Client, Collection, IndexModel, bson::{doc, Document}};
+use std::{collections::HashSet, sync::Mutex};
+use tracing::warn;
 use crate::domain::{error::IngestionError, ports::DataRepository};
 
 pub struct DocumentDBDataRepository {
     client: Client,
     database_name: String,
+    /// Collections whose `log_id` index has been ensured by this process.
+    indexed: Mutex<HashSet<String>>,
 }
 
 impl DocumentDBDataRepository {
     pub fn new(client: Client, database_name: String) -> Self {
-        Self { client, database_name }
+        Self { client, database_name, indexed: Mutex::new(HashSet::new()) }
+    }
+
+    /// Indexes `log_id` once per collection, so that deleting the documents of a log stays cheap.
+    async fn ensure_log_id_index(&self, collection: &Collection<Document>) {
+        if self.indexed.lock().unwrap().contains(collection.name()) {
+            return;
+        }
+        
+        let index = IndexModel::builder().keys(doc! { "log_id": 1 }).build();
+        match collection.create_index(index, None).await {
+            Ok(_) => { self.indexed.lock().unwrap().insert(collection.name().to_string()); },
+            Err(e) => warn!("Failed to create log_id index on {}: {}", collection.name(), e),
+        }
     }
 }
 
@@ -18,7 +34,8 @@ impl DocumentDBDataRepository {
 impl DataRepository for DocumentDBDataRepository {
     async fn insert_documents(&self, target_table: &str, documents: &[serde_json::Value], log_id: &str) -> Result<Vec<String>, IngestionError> {
         let db = self.client.database(&self.database_name);
-        let collection: Collection<mongodb::bson::Document> = db.collection(target_table);
+        let collection: Collection<Document> = db.collection(target_table);
+        self.ensure_log_id_index(&collection).await;
         
         let mut ids = Vec::new();
         let mut docs_to_insert = Vec::new();
@@ -42,4 +59,14 @@ impl DataRepository for DocumentDBDataRepository {
         
         Ok(ids)
     }
-}
\ No newline at end of file
+
+    async fn delete_documents(&self, target_table: &str, log_id: &str) -> Result<u64, IngestionError> {
+        let db = self.client.database(&self.database_name);
+        let collection: Collection<mongodb::bson::Document> = db.collection(target_table);
+        
+        let result = collection.delete_many(doc! { "log_id": log_id }, None).await
+            .map_err(|e| IngestionError::Database(e.to_string()))?;
+        
+        Ok(result.deleted_count)
+    }
+}
@@ -1,6 +1,10 @@
 pub mod s3_adapter;
 pub mod parser_adapter;
+pub mod decompression_adapter;
 pub mod parsers;
+pub mod rule_validator;
+pub mod rule_cache;
 pub mod mongodb;
 pub mod couchdb;
-pub mod documentdb;
\ No newline at end of file
+pub mod documentdb;
+pub(crate) mod spool;
\ No newline at end of file
@@ -1,15 +1,23 @@
+use std::sync::Arc;
+use std::time::Duration;
 use async_trait::async_trait;
-use mongodb::{Client, Collection, bson::doc};
-use regex::Regex;
+use futures_util::StreamExt;
+use mongodb::{Client, Collection, bson::{doc, Document}, options::ChangeStreamOptions};
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn, error};
 use crate::domain::{
     error::IngestionError,
-    models::IngestionConfigRule,
+    models::{FileMetadata, FileToProcess, IngestionConfigRule},
     ports::ConfigRepository,
+    rule_matching::FanoutMode,
 };
+use crate::infrastructure::{rule_cache::RuleCache, rule_validator::RuleValidator};
 
 pub struct MongoConfigRepository {
     collection: Collection<IngestionConfigRule>,
+    validator: RuleValidator,
+    fanout: FanoutMode,
+    cache: Arc<RuleCache>,
 }
 
 impl MongoConfigRepository {
@@ -17,15 +25,95 @@ impl MongoConfigRepository {
         debug!("Initializing MongoDB config repository for database: {}", database);
         let collection = client.database(database).collection("ingestion_config");
         debug!("MongoDB config repository initialized");
-        Self { collection }
+        Self { collection, validator: RuleValidator::default(), fanout: FanoutMode::default(), cache: Arc::new(RuleCache::default()) }
     }
+
+    /// Validates rules against `validator` instead of the built-in parsers.
+    pub fn with_validator(mut self, validator: RuleValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Applies every matching rule instead of only those flagged `fanout` when set to `FanoutMode::All`.
+    pub fn with_fanout_mode(mut self, fanout: FanoutMode) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    /// Reloads cached rules after `ttl` instead of the default.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache = Arc::new(RuleCache::new(ttl));
+        self
+    }
+
+    /// Invalidates the rule cache whenever the config collection changes.
+    pub fn spawn_change_listener(&self) -> JoinHandle<()> {
+        watch_rule_changes(self.collection.clone_with_type(), self.cache.clone())
+    }
+}
+
+/// Delay before re-opening a failed change stream, doubled after every failed attempt.
+const INITIAL_WATCH_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_WATCH_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Listens to a change stream on `collection` and drops `cache` on every change.
+///
+/// Change streams need a replica set; without one the listener stops with a warning and
+/// the cache falls back to its TTL. A stream that fails later is re-opened with backoff,
+/// resuming after the last change it delivered.
+pub(crate) fn watch_rule_changes(collection: Collection<Document>, cache: Arc<RuleCache>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut changes = match collection.watch(None, None).await {
+            Ok(changes) => changes,
+            Err(e) => {
+                warn!("Config change stream unavailable, relying on cache TTL: {}", e);
+                return;
+            }
+        };
+        info!("Watching {} for config rule changes", collection.name());
+
+        let mut backoff = INITIAL_WATCH_BACKOFF;
+        loop {
+            while let Some(change) = changes.next().await {
+                match change {
+                    Ok(event) => {
+                        debug!("Config rule change detected: {:?}", event.operation_type);
+                        cache.invalidate().await;
+                        backoff = INITIAL_WATCH_BACKOFF;
+                    },
+                    Err(e) => {
+                        warn!("Config change stream failed: {}", e);
+                        break;
+                    },
+                }
+            }
+
+            let mut resume_token = changes.resume_token();
+            changes = loop {
+                warn!("Re-opening config change stream in {:?}", backoff);
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_WATCH_BACKOFF);
+
+                let options = ChangeStreamOptions::builder().resume_after(resume_token.clone()).build();
+                match collection.watch(None, options).await {
+                    Ok(changes) => break changes,
+                    Err(e) => {
+                        // The token may have left the oplog; the cache is dropped once the stream is back
+                        warn!("Failed to re-open config change stream: {}", e);
+                        resume_token = None;
+                    },
+                }
+            };
+            info!("✅ Re-opened config change stream on {}", collection.name());
+            // Covers changes made while no stream was open
+            cache.invalidate().await;
+        }
+    })
 }
 
 #[async_trait]
 impl ConfigRepository for MongoConfigRepository {
-    async fn get_config_for_key(&self, s3_key: &str) -> Result<Option<IngestionConfigRule>, IngestionError> {
-        debug!("Searching for config rule matching S3 key: {}", s3_key);
-        
+    async fn load_rules(&self) -> Result<Vec<IngestionConfigRule>, IngestionError> {
         let mut cursor = self.collection
             .find(doc! {}, None)
             .await
@@ -34,9 +122,7 @@ impl ConfigRepository for MongoConfigRepository {
                 IngestionError::Database(e.to_string())
             })?;
 
-        let mut matching_rules = Vec::new();
-        let mut rules_checked = 0;
-        
+        let mut rules = Vec::new();
         while cursor.advance().await.map_err(|e| {
             error!("Failed to advance cursor: {}", e);
             IngestionError::Database(e.to_string())
@@ -46,39 +132,34 @@ impl ConfigRepository for MongoConfigRepository {
                     error!("Failed to deserialize config rule: {}", e);
                     IngestionError::Database(e.to_string())
                 })?;
-            
-            rules_checked += 1;
-            debug!("Checking rule {}: pattern='{}', target_table='{}'", 
-                rules_checked, rule.pattern, rule.target_table);
-            
-            let regex = Regex::new(&rule.pattern)
-                .map_err(|e| {
-                    error!("Invalid regex pattern '{}': {}", rule.pattern, e);
-                    IngestionError::Config(e.to_string())
-                })?;
-            
-            if regex.is_match(s3_key) {
-                debug!("✅ Rule pattern '{}' matches key '{}'", rule.pattern, s3_key);
-                matching_rules.push(rule);
-            } else {
-                debug!("❌ Rule pattern '{}' does not match key '{}'", rule.pattern, s3_key);
-            }
+            self.validator.validate(&rule)?;
+            rules.push(rule);
         }
+
+        debug!("Loaded {} config rules", rules.len());
+        Ok(rules)
+    }
+
+    async fn get_config_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Option<IngestionConfigRule>, IngestionError> {
+        debug!("Searching for config rule matching S3 object: {}/{}", file.bucket, file.key);
         
-        if matching_rules.is_empty() {
-            warn!("No matching configuration rule found for '{}' after checking {} rules", s3_key, rules_checked);
+        let rules = self.cache.get_or_load(|| self.load_rules()).await?;
+        let Some(best_rule) = rules.select(file, metadata).cloned() else {
+            warn!("No matching configuration rule found for '{}/{}' after checking {} rules", file.bucket, file.key, rules.rules().len());
             return Ok(None);
-        }
-        
-        // Select the most specific rule (longest pattern)
-        let best_rule = matching_rules
-            .into_iter()
-            .max_by_key(|rule| rule.pattern.len())
-            .unwrap();
+        };
         
-        info!("✅ Best matching rule for '{}': pattern='{}', target_table='{}'", 
-            s3_key, best_rule.pattern, best_rule.target_table);
+        info!("✅ Best matching rule for '{}/{}': pattern='{}', target_table='{}', priority={}", 
+            file.bucket, file.key, best_rule.pattern, best_rule.target_table, best_rule.priority);
         
         Ok(Some(best_rule))
     }
+
+    async fn get_configs_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        let rules = self.cache.get_or_load(|| self.load_rules()).await?;
+        Ok(rules.select_targets(file, metadata, self.fanout).into_iter().cloned().collect())
+    }
+    async fn uses_tags(&self) -> Result<bool, IngestionError> {
+        Ok(self.cache.get_or_load(|| self.load_rules()).await?.uses_tags())
+    }
 }
\ No newline at end of file
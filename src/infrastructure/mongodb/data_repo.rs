@@ -1,17 +1,36 @@
 use async_trait::async_trait;
-use mongodb::{Client, Collection, bson::Document};
-use tracing::{debug, info, error};
+use mongodb::{Client, Collection, IndexModel, bson::{doc, Document}};
+use std::{collections::HashSet, sync::Mutex};
+use tracing::{debug, info, warn, error};
 use crate::domain::{error::IngestionError, ports::DataRepository};
 
 pub struct MongoDataRepository {
     client: Client,
     database: String,
+    /// Collections whose `log_id` index has been ensured by this process.
+    indexed: Mutex<HashSet<String>>,
 }
 
 impl MongoDataRepository {
     pub fn new(client: Client, database: String) -> Self {
         debug!("Initializing MongoDB data repository for database: {}", database);
-        Self { client, database }
+        Self { client, database, indexed: Mutex::new(HashSet::new()) }
+    }
+
+    /// Indexes `log_id` once per collection, so that deleting the documents of a log stays cheap.
+    async fn ensure_log_id_index(&self, collection: &Collection<Document>) {
+        if self.indexed.lock().unwrap().contains(collection.name()) {
+            return;
+        }
+        
+        let index = IndexModel::builder().keys(doc! { "log_id": 1 }).build();
+        match collection.create_index(index, None).await {
+            Ok(_) => {
+                debug!("Ensured log_id index on collection: {}", collection.name());
+                self.indexed.lock().unwrap().insert(collection.name().to_string());
+            },
+            Err(e) => warn!("Failed to create log_id index on {}: {}", collection.name(), e),
+        }
     }
 }
 
@@ -27,6 +46,7 @@ impl DataRepository for MongoDataRepository {
         
         let collection: Collection<Document> = self.client.database(&self.database).collection(target_table);
         debug!("Connected to collection: {}.{}", self.database, target_table);
+        self.ensure_log_id_index(&collection).await;
         
         debug!("Converting {} JSON documents to BSON and adding log_id: {}", documents.len(), log_id);
         let docs: Vec<Document> = documents
@@ -68,4 +88,20 @@ impl DataRepository for MongoDataRepository {
         
         Ok(ids)
     }
-}
\ No newline at end of file
+
+    async fn delete_documents(&self, target_table: &str, log_id: &str) -> Result<u64, IngestionError> {
+        debug!("Deleting documents with log_id {} from collection: {}", log_id, target_table);
+        
+        let collection: Collection<Document> = self.client.database(&self.database).collection(target_table);
+        let result = collection
+            .delete_many(doc! { "log_id": log_id }, None)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete documents of log {} from {}: {}", log_id, target_table, e);
+                IngestionError::Database(e.to_string())
+            })?;
+        
+        info!("✅ Deleted {} documents of log {} from collection: {}", result.deleted_count, log_id, target_table);
+        Ok(result.deleted_count)
+    }
+}
@@ -1,8 +1,9 @@
 use async_trait::async_trait;
-use mongodb::{Client, Collection, bson::Document};
+use futures_util::TryStreamExt;
+use mongodb::{Client, Collection, IndexModel, bson::Document, options::{FindOptions, IndexOptions}};
 use tracing::{debug, info, error};
 use chrono::{DateTime, Utc};
-use crate::domain::{error::IngestionError, models::{IngestionLog, IngestionStatus}, ports::LogRepository};
+use crate::domain::{error::IngestionError, models::{DocumentCounts, IngestionLog, IngestionStatus, SourceVersion}, ports::LogRepository};
 
 pub struct MongoLogRepository {
     client: Client,
@@ -14,6 +15,32 @@ impl MongoLogRepository {
         debug!("Initializing MongoDB log repository for database: {}", database);
         Self { client, database }
     }
+
+    /// Creates the index that duplicate detection looks earlier logs up by; a no-op when it exists.
+    pub async fn create_indexes(&self) -> Result<(), IngestionError> {
+        use mongodb::bson::doc;
+        
+        let collection: Collection<Document> = self.client.database(&self.database).collection("ingestion_logs");
+        let index = IndexModel::builder()
+            .keys(doc! {
+                "target_table": 1,
+                "source.bucket": 1,
+                "source.key": 1,
+                "source.version_id": 1,
+                "source.etag": 1,
+                "start_time": 1,
+            })
+            .options(IndexOptions::builder().name("source_version".to_string()).build())
+            .build();
+        collection.create_index(index, None).await
+            .map_err(|e| {
+                error!("Failed to create index on ingestion_logs: {}", e);
+                IngestionError::Database(e.to_string())
+            })?;
+        
+        info!("✅ Ensured source version index on ingestion_logs");
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -46,7 +73,7 @@ impl LogRepository for MongoLogRepository {
         Ok(log_id)
     }
     
-    async fn update_log(&self, log_id: &str, end_time: DateTime<Utc>, status: IngestionStatus, message: Option<String>) -> Result<(), IngestionError> {
+    async fn update_log(&self, log_id: &str, end_time: DateTime<Utc>, status: IngestionStatus, message: Option<String>, counts: DocumentCounts) -> Result<(), IngestionError> {
         use mongodb::bson::{doc, oid::ObjectId};
         
         debug!("Updating log with ID: {}", log_id);
@@ -62,7 +89,9 @@ impl LogRepository for MongoLogRepository {
             "$set": {
                 "end_time": mongodb::bson::to_bson(&end_time).unwrap(),
                 "status": mongodb::bson::to_bson(&status).unwrap(),
-                "message": message
+                "message": message,
+                "accepted_count": counts.accepted as i64,
+                "rejected_count": counts.rejected as i64
             }
         };
         
@@ -86,4 +115,42 @@ impl LogRepository for MongoLogRepository {
         info!("✅ Successfully updated log with ID: {}", log_id);
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn find_logs(&self, source: &SourceVersion, target_table: &str) -> Result<Vec<(String, IngestionLog)>, IngestionError> {
+        use mongodb::bson::{doc, from_document, Bson};
+        
+        debug!("Finding earlier logs of s3://{}/{} for table {}", source.bucket, source.key, target_table);
+        let collection: Collection<Document> = self.client.database(&self.database).collection("ingestion_logs");
+        
+        let filter = doc! {
+            "target_table": target_table,
+            "source.bucket": &source.bucket,
+            "source.key": &source.key,
+            "source.version_id": source.version_id.as_deref().map_or(Bson::Null, Bson::from),
+            "source.etag": source.etag.as_deref().map_or(Bson::Null, Bson::from),
+        };
+        let options = FindOptions::builder().sort(doc! { "start_time": 1 }).build();
+        
+        let mut cursor = collection.find(filter, options).await
+            .map_err(|e| {
+                error!("Failed to query logs of {}: {}", source.key, e);
+                IngestionError::Database(e.to_string())
+            })?;
+        
+        let mut logs = Vec::new();
+        while let Some(document) = cursor.try_next().await.map_err(|e| IngestionError::Database(e.to_string()))? {
+            let log_id = document.get_object_id("_id")
+                .map(|oid| oid.to_hex())
+                .map_err(|e| IngestionError::Database(format!("Invalid log _id: {}", e)))?;
+            let log: IngestionLog = from_document(document)
+                .map_err(|e| {
+                    error!("Failed to read log {}: {}", log_id, e);
+                    IngestionError::Database(e.to_string())
+                })?;
+            logs.push((log_id, log));
+        }
+        
+        debug!("Found {} earlier logs of {}", logs.len(), source.key);
+        Ok(logs)
+    }
+}
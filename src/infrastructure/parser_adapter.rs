@@ -1,22 +1,36 @@
 use async_trait::async_trait;
 use tracing::{debug, info, error};
 use crate::{
-    domain::{error::IngestionError, ports::DataParser},
+    domain::{error::IngestionError, ports::{DataParser, DocumentBatchStream, FileStream}},
     infrastructure::parsers::{
-        csv_parser::{parse_csv, parse_csv_with_config},
-        json_parser::parse_json,
-        txt_parser::parse_txt,
-        xml_parser::parse_xml,
-        excel_parser::parse_excel,
+        registry::ParserRegistry,
+        streaming::batch_size_from_config,
     }
 };
 
-pub struct ParserAdapter;
+/// Dispatches parsing to the parser registered for each file type.
+pub struct ParserAdapter {
+    registry: ParserRegistry,
+}
 
 impl ParserAdapter {
     pub fn new() -> Self {
-        debug!("Initializing parser adapter");
-        Self
+        Self::with_registry(ParserRegistry::with_builtin_parsers())
+    }
+
+    pub fn with_registry(registry: ParserRegistry) -> Self {
+        debug!("Initializing parser adapter with {} parsers", registry.parsers().len());
+        Self { registry }
+    }
+
+    pub fn registry(&self) -> &ParserRegistry {
+        &self.registry
+    }
+}
+
+impl Default for ParserAdapter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -29,29 +43,12 @@ impl DataParser for ParserAdapter {
     async fn parse_with_config(&self, file_bytes: &[u8], file_type: &str, config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
         info!("Parsing file with type: {} ({} bytes)", file_type, file_bytes.len());
         
-        let result = match file_type {
-            "csv" => {
-                debug!("Parsing CSV file with config: {:?}", config);
-                parse_csv_with_config(file_bytes, config)
-            },
-            "json" => {
-                debug!("Parsing JSON file");
-                parse_json(file_bytes)
-            },
-            "txt" => {
-                debug!("Parsing text file");
-                parse_txt(file_bytes)
+        let result = match self.registry.resolve(file_type, config) {
+            Some(parser) => {
+                debug!("Parsing {} file with the {} parser and config: {:?}", file_type, parser.name(), config);
+                parser.parse(file_bytes, config)
             },
-            "xml" => {
-                debug!("Parsing XML file");
-                parse_xml(file_bytes)
-            },
-            "xls" | "xlsx" => {
-                debug!("Parsing Excel file ({})", file_type);
-                parse_excel(file_bytes)
-            },
-
-            _ => {
+            None => {
                 error!("Unsupported file type: {}", file_type);
                 Err(IngestionError::Parse(format!("Unsupported file type: {}", file_type)))
             }
@@ -73,5 +70,32 @@ impl DataParser for ParserAdapter {
         
         result
     }
-}
 
+    fn supports_streaming(&self, file_type: &str, config: Option<&serde_json::Value>) -> bool {
+        self.registry.resolve(file_type, config).is_some_and(|parser| parser.supports_streaming())
+    }
+
+    fn file_types(&self) -> Vec<String> {
+        self.registry.file_types()
+    }
+
+    fn file_type_for_content_type(&self, content_type: &str) -> Option<String> {
+        self.registry.file_type_for_mime(content_type)
+    }
+
+    async fn parse_stream(&self, stream: FileStream, file_type: &str, config: Option<&serde_json::Value>) -> Result<DocumentBatchStream, IngestionError> {
+        let batch_size = batch_size_from_config(config);
+        info!("Streaming {} file in batches of {} documents", file_type, batch_size);
+
+        match self.registry.resolve(file_type, config).filter(|parser| parser.supports_streaming()) {
+            Some(parser) => {
+                debug!("Streaming {} file with the {} parser and config: {:?}", file_type, parser.name(), config);
+                parser.parse_stream(stream, config.cloned(), batch_size)
+            },
+            None => {
+                error!("Streaming is not supported for file type: {}", file_type);
+                Err(IngestionError::Parse(format!("Streaming is not supported for file type: {}", file_type)))
+            }
+        }
+    }
+}
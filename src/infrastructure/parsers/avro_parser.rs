@@ -0,0 +1,181 @@
+use std::io::{Cursor, Read};
+use apache_avro::{schema::{NamesRef, ResolvedSchema}, types::Value as AvroValue, Reader, Schema};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde_json::Value;
+use tracing::{debug, info, error};
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream}};
+use crate::infrastructure::parsers::{coercion::{decimal_bytes_to_json, decimal_text_to_json, decimals_as_string}, streaming::spawn_batched};
+
+pub fn parse_avro(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
+    parse_avro_with_config(bytes, None)
+}
+
+pub fn parse_avro_with_config(bytes: &[u8], config: Option<&Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
+    let as_string = decimals_as_string(config);
+    let mut documents = Vec::new();
+    read_avro_records(Cursor::new(bytes), as_string, &mut |doc| {
+        documents.push(doc);
+        Ok(())
+    })?;
+    Ok(documents)
+}
+
+/// Decodes an Avro object container file block by block, yielding documents in batches of `batch_size`.
+pub fn stream_avro_with_config(stream: FileStream, config: Option<Value>, batch_size: usize) -> DocumentBatchStream {
+    let as_string = decimals_as_string(config.as_ref());
+    spawn_batched(stream, batch_size, move |reader, emit| read_avro_records(reader, as_string, emit))
+}
+
+fn read_avro_records<R: Read>(
+    source: R,
+    decimals_as_string: bool,
+    emit: &mut dyn FnMut(serde_json::Value) -> Result<(), IngestionError>,
+) -> Result<(), IngestionError> {
+    let reader = Reader::new(source).map_err(|e| {
+        error!("Failed to read Avro container header: {}", e);
+        IngestionError::Parse(e.to_string())
+    })?;
+
+    // The embedded writer schema carries decimal scales and union branches
+    let schema = reader.writer_schema().clone();
+    let resolved = ResolvedSchema::try_from(&schema).map_err(|e| {
+        error!("Invalid Avro writer schema: {}", e);
+        IngestionError::Parse(e.to_string())
+    })?;
+    let names = resolved.get_names();
+    debug!("Avro writer schema: {}", schema.canonical_form());
+
+    let mut record_count = 0;
+    for value in reader {
+        let value = value.map_err(|e| {
+            error!("Failed to decode Avro record {}: {}", record_count + 1, e);
+            IngestionError::Parse(e.to_string())
+        })?;
+        let document = avro_to_json(value, &schema, names, decimals_as_string).map_err(|e| {
+            error!("Failed to convert Avro record {}: {}", record_count + 1, e);
+            e
+        })?;
+        emit(document)?;
+        record_count += 1;
+    }
+
+    info!("Parsed {} records from Avro", record_count);
+    Ok(())
+}
+
+fn avro_to_json(value: AvroValue, schema: &Schema, names: &NamesRef, decimals_as_string: bool) -> Result<Value, IngestionError> {
+    // Named types may be referenced after their first definition
+    let schema = match schema {
+        Schema::Ref { name } => names.get(name).copied().unwrap_or(schema),
+        other => other,
+    };
+
+    let json = match value {
+        AvroValue::Null => Value::Null,
+        AvroValue::Boolean(b) => Value::Bool(b),
+        AvroValue::Int(i) => Value::from(i),
+        AvroValue::Long(l) => Value::from(l),
+        AvroValue::Float(f) => float_to_json(f as f64),
+        AvroValue::Double(d) => float_to_json(d),
+        AvroValue::Bytes(bytes) | AvroValue::Fixed(_, bytes) => Value::String(BASE64.encode(bytes)),
+        AvroValue::String(s) | AvroValue::Enum(_, s) => Value::String(s),
+        AvroValue::Uuid(uuid) => Value::String(uuid.to_string()),
+        AvroValue::Union(index, inner) => {
+            let branch = match schema {
+                Schema::Union(union) => union.variants().get(index as usize).unwrap_or(schema),
+                other => other,
+            };
+            avro_to_json(*inner, branch, names, decimals_as_string)?
+        },
+        AvroValue::Array(items) => {
+            let item_schema = match schema {
+                Schema::Array(array) => array.items.as_ref(),
+                other => other,
+            };
+            Value::Array(items.into_iter().map(|item| avro_to_json(item, item_schema, names, decimals_as_string)).collect::<Result<_, _>>()?)
+        },
+        AvroValue::Map(entries) => {
+            let value_schema = match schema {
+                Schema::Map(map) => map.types.as_ref(),
+                other => other,
+            };
+            Value::Object(entries.into_iter().map(|(k, v)| Ok((k, avro_to_json(v, value_schema, names, decimals_as_string)?))).collect::<Result<_, IngestionError>>()?)
+        },
+        AvroValue::Record(fields) => {
+            let record_schema = match schema {
+                Schema::Record(record) => Some(record),
+                _ => None,
+            };
+            Value::Object(
+                fields.into_iter()
+                    .map(|(name, field)| {
+                        let field_schema = record_schema
+                            .and_then(|r| r.lookup.get(&name).map(|&i| &r.fields[i].schema))
+                            .unwrap_or(schema);
+                        let json = avro_to_json(field, field_schema, names, decimals_as_string).map_err(|e| match e {
+                            IngestionError::Parse(message) => IngestionError::Parse(format!("field '{}': {}", name, message)),
+                            other => other,
+                        })?;
+                        Ok((name, json))
+                    })
+                    .collect::<Result<_, IngestionError>>()?,
+            )
+        },
+        AvroValue::Decimal(decimal) => {
+            let scale = match schema {
+                Schema::Decimal(decimal_schema) => decimal_schema.scale as u32,
+                _ => 0,
+            };
+            let bytes = Vec::<u8>::try_from(&decimal)
+                .map_err(|e| IngestionError::Parse(format!("cannot decode decimal: {}", e)))?;
+            decimal_bytes_to_json(&bytes, scale, decimals_as_string)
+        },
+        AvroValue::BigDecimal(decimal) => decimal_text_to_json(decimal.to_string(), decimals_as_string),
+        AvroValue::Date(days) => NaiveDate::from_num_days_from_ce_opt(days + EPOCH_DAYS_FROM_CE)
+            .map(|d| Value::String(d.format("%Y-%m-%d").to_string()))
+            .unwrap_or(Value::Null),
+        AvroValue::TimeMillis(ms) => time_to_json(ms as i64 * 1_000_000),
+        AvroValue::TimeMicros(us) => time_to_json(us * 1_000),
+        AvroValue::TimestampMillis(ms) => timestamp_to_json(DateTime::<Utc>::from_timestamp_millis(ms), true),
+        AvroValue::TimestampMicros(us) => timestamp_to_json(DateTime::<Utc>::from_timestamp_micros(us), true),
+        AvroValue::TimestampNanos(ns) => timestamp_to_json(Some(DateTime::<Utc>::from_timestamp_nanos(ns)), true),
+        AvroValue::LocalTimestampMillis(ms) => timestamp_to_json(DateTime::<Utc>::from_timestamp_millis(ms), false),
+        AvroValue::LocalTimestampMicros(us) => timestamp_to_json(DateTime::<Utc>::from_timestamp_micros(us), false),
+        AvroValue::LocalTimestampNanos(ns) => timestamp_to_json(Some(DateTime::<Utc>::from_timestamp_nanos(ns)), false),
+        AvroValue::Duration(duration) => serde_json::json!({
+            "months": u32::from(duration.months()),
+            "days": u32::from(duration.days()),
+            "millis": u32::from(duration.millis()),
+        }),
+    };
+    Ok(json)
+}
+
+/// Days between 0001-01-01 and the Unix epoch.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+fn float_to_json(f: f64) -> Value {
+    serde_json::Number::from_f64(f).map(Value::Number).unwrap_or(Value::Null)
+}
+
+fn time_to_json(nanos_since_midnight: i64) -> Value {
+    let secs = nanos_since_midnight.div_euclid(1_000_000_000) as u32;
+    let nanos = nanos_since_midnight.rem_euclid(1_000_000_000) as u32;
+    NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos)
+        .map(|t| Value::String(t.format("%H:%M:%S%.f").to_string()))
+        .unwrap_or(Value::Null)
+}
+
+/// Timestamps render as RFC 3339; local timestamps carry no offset.
+fn timestamp_to_json(ts: Option<DateTime<Utc>>, utc: bool) -> Value {
+    ts.map(|dt| {
+        if utc {
+            dt.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+        } else {
+            dt.naive_utc().format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+        }
+    })
+    .map(Value::String)
+    .unwrap_or(Value::Null)
+}
@@ -0,0 +1,396 @@
+use serde_json::Value;
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream}};
+use crate::infrastructure::parsers::{
+    avro_parser::{parse_avro_with_config, stream_avro_with_config},
+    csv_parser::{parse_csv_with_config, stream_csv_with_config},
+    excel_parser::parse_excel_with_config,
+    fixed_width_parser::{has_fixed_width_fields, parse_fixed_width_with_config, stream_fixed_width_with_config},
+    json_parser::parse_json_with_config,
+    log_parser::{has_line_pattern, parse_log_with_config},
+    ndjson_parser::{parse_ndjson_with_config, stream_ndjson_with_config},
+    parquet_parser::{parse_parquet_with_config, stream_parquet_with_config},
+    pdf_parser::parse_pdf_with_config,
+    registry::{ConfigOption, FileParser, OptionType::*, ParserRegistry},
+    txt_parser::parse_txt_with_config,
+    xml_parser::parse_xml_with_config,
+};
+
+const ENCODING: ConfigOption = ConfigOption::new("encoding", &[String], "Character encoding such as windows-1252 or utf-16le; detected when omitted or 'auto'");
+const BATCH_SIZE: ConfigOption = ConfigOption::new("batch_size", &[Integer], "Documents per stored batch when streaming");
+const COLUMNS: ConfigOption = ConfigOption::new("columns", &[Object], "Column name to type name or {type, format, scale, as_string, null_values, decimal_separator}");
+const DECIMALS_AS_STRING: ConfigOption = ConfigOption::new("decimals_as_string", &[Boolean], "Store decimal values as exact text instead of numbers");
+const NULL_VALUES: ConfigOption = ConfigOption::new("null_values", &[Array], "Raw values stored as null in typed columns");
+
+/// Registers every parser shipped with this crate.
+pub fn register_all(registry: &mut ParserRegistry) {
+    registry
+        .register(CsvParser)
+        .register(JsonParser)
+        .register(NdjsonParser)
+        .register(TxtParser)
+        .register(FixedWidthParser)
+        .register(LogParser)
+        .register(XmlParser)
+        .register(ExcelParser)
+        .register(ParquetParser)
+        .register(AvroParser)
+        .register(PdfParser);
+}
+
+pub struct CsvParser;
+
+impl FileParser for CsvParser {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["csv"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/csv", "application/csv", "text/comma-separated-values"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("headers", &[Array], "Column names to use instead of the header row"),
+            ConfigOption::new("has_headers", &[Boolean], "Whether the first row is a header row"),
+            ConfigOption::new("delimiter", &[String], "Field delimiter, a single character or 'tab'"),
+            ConfigOption::new("quote", &[String], "Quote character"),
+            ConfigOption::new("escape", &[String], "Escape character"),
+            ConfigOption::new("double_quote", &[Boolean], "Whether doubled quotes are an escaped quote"),
+            ConfigOption::new("comment", &[String], "Lines starting with this character are skipped"),
+            ConfigOption::new("skip_rows", &[Integer], "Lines to skip before the CSV content"),
+            ConfigOption::new("trim", &[Boolean, String], "Whitespace trimming: none, all, headers or fields"),
+            ConfigOption::new("flexible", &[Boolean], "Allow rows with differing field counts"),
+            COLUMNS,
+            NULL_VALUES,
+            ConfigOption::new("infer_types", &[Boolean], "Infer types of columns without an explicit type"),
+            ConfigOption::new("infer_sample_rows", &[Integer], "Rows sampled for type inference"),
+            ENCODING,
+            BATCH_SIZE,
+        ]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_csv_with_config(bytes, config)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn parse_stream(&self, stream: FileStream, config: Option<Value>, batch_size: usize) -> Result<DocumentBatchStream, IngestionError> {
+        Ok(stream_csv_with_config(stream, config, batch_size))
+    }
+}
+
+pub struct JsonParser;
+
+impl FileParser for JsonParser {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["json"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/json", "text/json"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("records_path", &[String], "JSON Pointer or dotted path of the records array"),
+            ConfigOption::new("metadata_fields", &[Array, Object], "Paths copied from the document into every record that has no field of the same name"),
+            ENCODING,
+        ]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_json_with_config(bytes, config)
+    }
+}
+
+pub struct NdjsonParser;
+
+impl FileParser for NdjsonParser {
+    fn name(&self) -> &str {
+        "ndjson"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["ndjson", "jsonl"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/x-ndjson", "application/ndjson", "application/jsonl", "application/x-jsonlines", "application/jsonlines"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("on_error", &[String], "Malformed lines: fail or skip"),
+            ENCODING,
+            BATCH_SIZE,
+        ]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_ndjson_with_config(bytes, config)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn parse_stream(&self, stream: FileStream, config: Option<Value>, batch_size: usize) -> Result<DocumentBatchStream, IngestionError> {
+        Ok(stream_ndjson_with_config(stream, config, batch_size))
+    }
+}
+
+pub struct TxtParser;
+
+impl FileParser for TxtParser {
+    fn name(&self) -> &str {
+        "txt"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["txt", "log"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![ENCODING]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_txt_with_config(bytes, config)
+    }
+}
+
+pub struct FixedWidthParser;
+
+impl FileParser for FixedWidthParser {
+    fn name(&self) -> &str {
+        "fixed_width"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["fixed", "txt"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("fields", &[Array], "Fields as {name, start, length or end, type, trim}"),
+            ConfigOption::new("skip_rows", &[Integer], "Lines to skip before the records"),
+            NULL_VALUES,
+            ENCODING,
+            BATCH_SIZE,
+        ]
+    }
+
+    fn accepts(&self, file_type: &str, config: Option<&Value>) -> bool {
+        file_type == "fixed" || has_fixed_width_fields(config)
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_fixed_width_with_config(bytes, config)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn parse_stream(&self, stream: FileStream, config: Option<Value>, batch_size: usize) -> Result<DocumentBatchStream, IngestionError> {
+        Ok(stream_fixed_width_with_config(stream, config, batch_size))
+    }
+}
+
+pub struct LogParser;
+
+impl FileParser for LogParser {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["log", "txt"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("line_pattern", &[Regex], "Regex with named captures matching the first line of an entry"),
+            ConfigOption::new("continuation_pattern", &[Regex], "Regex for lines appended to the previous entry"),
+            ConfigOption::new("continuation_field", &[String], "Field holding continuation lines"),
+            ConfigOption::new("on_unmatched", &[String], "Other lines: attach, skip or fail"),
+            COLUMNS,
+            NULL_VALUES,
+            ENCODING,
+        ]
+    }
+
+    fn accepts(&self, _file_type: &str, config: Option<&Value>) -> bool {
+        has_line_pattern(config)
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_log_with_config(bytes, config)
+    }
+}
+
+pub struct XmlParser;
+
+impl FileParser for XmlParser {
+    fn name(&self) -> &str {
+        "xml"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["xml"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/xml", "text/xml"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("record_path", &[String], "Record element: a name, /absolute/path or //suffix"),
+            ConfigOption::new("include_attributes", &[Boolean], "Whether attributes become fields"),
+            ConfigOption::new("attribute_prefix", &[String], "Prefix for attribute field names, '@' by default so attributes never collide with child elements"),
+            ConfigOption::new("text_key", &[String], "Field for text of elements that also have attributes or children"),
+            ConfigOption::new("namespaces", &[String], "Namespace prefixes: strip or keep"),
+        ]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_xml_with_config(bytes, config)
+    }
+}
+
+pub struct ExcelParser;
+
+impl FileParser for ExcelParser {
+    fn name(&self) -> &str {
+        "excel"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["xlsx", "xls", "xlsm", "xlsb", "ods"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &[
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "application/vnd.ms-excel",
+            "application/vnd.oasis.opendocument.spreadsheet",
+        ]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("sheet_name", &[String], "Worksheet to read"),
+            ConfigOption::new("sheet_index", &[Integer], "Zero-based worksheet to read"),
+            ConfigOption::new("all_sheets", &[Boolean], "Read every worksheet, tagging rows with sheet_name"),
+            ConfigOption::new("header_row", &[Integer], "Zero-based worksheet row holding the headers"),
+        ]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_excel_with_config(bytes, config)
+    }
+}
+
+pub struct ParquetParser;
+
+impl FileParser for ParquetParser {
+    fn name(&self) -> &str {
+        "parquet"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["parquet"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/vnd.apache.parquet", "application/x-parquet"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![DECIMALS_AS_STRING, BATCH_SIZE]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_parquet_with_config(bytes, config)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn parse_stream(&self, stream: FileStream, config: Option<Value>, batch_size: usize) -> Result<DocumentBatchStream, IngestionError> {
+        Ok(stream_parquet_with_config(stream, config, batch_size))
+    }
+}
+
+pub struct AvroParser;
+
+impl FileParser for AvroParser {
+    fn name(&self) -> &str {
+        "avro"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["avro"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/avro", "avro/binary", "application/vnd.apache.avro+binary"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![DECIMALS_AS_STRING, BATCH_SIZE]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_avro_with_config(bytes, config)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn parse_stream(&self, stream: FileStream, config: Option<Value>, batch_size: usize) -> Result<DocumentBatchStream, IngestionError> {
+        Ok(stream_avro_with_config(stream, config, batch_size))
+    }
+}
+
+pub struct PdfParser;
+
+impl FileParser for PdfParser {
+    fn name(&self) -> &str {
+        "pdf"
+    }
+
+    fn file_types(&self) -> &[&str] {
+        &["pdf"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/pdf"]
+    }
+
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new("mode", &[String], "pages for one document per page, tables for table rows"),
+            ConfigOption::new("min_columns", &[Integer], "Minimum cells for a line to count as a table row"),
+            ConfigOption::new("column_gap", &[Number], "Horizontal gap, in font sizes, between table cells"),
+        ]
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+        parse_pdf_with_config(bytes, config)
+    }
+}
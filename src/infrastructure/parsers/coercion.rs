@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use tracing::debug;
+use crate::domain::{dates::parse_date, error::IngestionError};
+
+pub const DEFAULT_INFER_SAMPLE_ROWS: usize = 100;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    /// Stored as a JSON number, or as its exact text with `as_string`.
+    Decimal { scale: Option<u32>, as_string: bool },
+    Date { format: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSpec {
+    pub column_type: ColumnType,
+    pub null_values: Option<Vec<String>>,
+    pub decimal_separator: Option<char>,
+}
+
+impl ColumnSpec {
+    pub fn new(column_type: ColumnType) -> Self {
+        Self { column_type, null_values: None, decimal_separator: None }
+    }
+
+    /// Builds a column spec from either a type name (`"integer"`) or an object
+    /// (`{"type": "date", "format": "%d/%m/%Y"}`).
+    pub fn from_config(name: &str, value: &Value) -> Result<Self, IngestionError> {
+        let (type_name, options) = match value {
+            Value::String(s) => (s.as_str(), None),
+            Value::Object(map) => {
+                let type_name = map.get("type")
+                    .and_then(|t| t.as_str())
+                    .ok_or_else(|| IngestionError::Config(format!("Column '{}' is missing a string 'type'", name)))?;
+                (type_name, Some(map))
+            },
+            _ => return Err(IngestionError::Config(format!("Column '{}' must be a type name or an object", name))),
+        };
+
+        let option_str = |key: &str| options.and_then(|o| o.get(key)).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        let column_type = match type_name {
+            "string" => ColumnType::String,
+            "integer" | "int" => ColumnType::Integer,
+            "float" | "number" => ColumnType::Float,
+            "boolean" | "bool" => ColumnType::Boolean,
+            "decimal" => ColumnType::Decimal {
+                scale: options.and_then(|o| o.get("scale")).and_then(|v| v.as_u64()).map(|s| s as u32),
+                as_string: options.and_then(|o| o.get("as_string")).and_then(|v| v.as_bool()).unwrap_or(false),
+            },
+            "date" | "datetime" => ColumnType::Date { format: option_str("format") },
+            other => return Err(IngestionError::Config(format!("Column '{}' has unknown type '{}'", name, other))),
+        };
+
+        let null_values = options
+            .and_then(|o| o.get("null_values"))
+            .map(|v| string_list(v, &format!("columns.{}.null_values", name)))
+            .transpose()?;
+
+        let decimal_separator = match option_str("decimal_separator") {
+            Some(sep) => {
+                let mut chars = sep.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => return Err(IngestionError::Config(format!("Column '{}' decimal_separator must be a single character", name))),
+                }
+            },
+            None => None,
+        };
+
+        Ok(Self { column_type, null_values, decimal_separator })
+    }
+}
+
+/// Column typing rules taken from a parser config: explicit `columns`,
+/// shared `null_values` and optional `infer_types` sampling.
+#[derive(Debug, Clone, Default)]
+pub struct TypeSchema {
+    columns: HashMap<String, ColumnSpec>,
+    null_values: Vec<String>,
+    infer_types: bool,
+    sample_rows: usize,
+}
+
+impl TypeSchema {
+    pub fn from_config(config: Option<&Value>) -> Result<Self, IngestionError> {
+        let mut schema = Self {
+            null_values: vec![String::new()],
+            sample_rows: DEFAULT_INFER_SAMPLE_ROWS,
+            ..Self::default()
+        };
+        let Some(config) = config else {
+            return Ok(schema);
+        };
+
+        if let Some(columns) = config.get("columns") {
+            let columns = columns.as_object()
+                .ok_or_else(|| IngestionError::Config("'columns' must be an object of column name to type".to_string()))?;
+            for (name, spec) in columns {
+                schema.columns.insert(name.clone(), ColumnSpec::from_config(name, spec)?);
+            }
+        }
+        if let Some(null_values) = config.get("null_values") {
+            schema.null_values = string_list(null_values, "null_values")?;
+        }
+        schema.infer_types = config.get("infer_types").and_then(|v| v.as_bool()).unwrap_or(false);
+        if let Some(rows) = config.get("infer_sample_rows").and_then(|v| v.as_u64()) {
+            schema.sample_rows = rows.max(1) as usize;
+        }
+
+        debug!("Type schema: {} explicit columns, infer_types={}", schema.columns.len(), schema.infer_types);
+        Ok(schema)
+    }
+
+    pub fn infer_types(&self) -> bool {
+        self.infer_types
+    }
+
+    pub fn sample_rows(&self) -> usize {
+        self.sample_rows
+    }
+
+    /// Picks a type for every column without an explicit spec, based on sampled values.
+    pub fn infer(&mut self, headers: &[String], rows: &[Vec<String>]) {
+        for (i, header) in headers.iter().enumerate() {
+            if self.columns.contains_key(header) {
+                continue;
+            }
+            let values = rows.iter()
+                .filter_map(|row| row.get(i))
+                .filter(|v| !self.null_values.contains(v))
+                .map(|v| v.as_str());
+            let column_type = infer_column_type(values);
+            debug!("Inferred type {:?} for column '{}'", column_type, header);
+            if column_type != ColumnType::String {
+                self.columns.insert(header.clone(), ColumnSpec::new(column_type));
+            }
+        }
+    }
+
+    /// Converts a raw field; columns without a spec stay strings.
+    pub fn convert(&self, column: &str, raw: &str) -> Result<Value, String> {
+        let Some(spec) = self.columns.get(column) else {
+            return Ok(Value::String(raw.to_string()));
+        };
+        let null_values = spec.null_values.as_ref().unwrap_or(&self.null_values);
+        if null_values.iter().any(|n| n == raw) {
+            return Ok(Value::Null);
+        }
+        coerce(raw, spec)
+    }
+}
+
+pub fn coerce(raw: &str, spec: &ColumnSpec) -> Result<Value, String> {
+    let trimmed = raw.trim();
+    let numeric = || match spec.decimal_separator {
+        Some(sep) => trimmed.replace(sep, "."),
+        None => trimmed.to_string(),
+    };
+
+    match &spec.column_type {
+        ColumnType::String => Ok(Value::String(raw.to_string())),
+        ColumnType::Integer => trimmed.parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("cannot convert '{}' to integer", raw)),
+        ColumnType::Float => numeric().parse::<f64>().ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| format!("cannot convert '{}' to float", raw)),
+        ColumnType::Boolean => parse_bool(trimmed)
+            .map(Value::Bool)
+            .ok_or_else(|| format!("cannot convert '{}' to boolean", raw)),
+        ColumnType::Decimal { scale, as_string } => {
+            let mut value = Decimal::from_str(&numeric())
+                .map_err(|_| format!("cannot convert '{}' to decimal", raw))?;
+            if let Some(scale) = scale {
+                value.rescale(*scale);
+            }
+            Ok(decimal_text_to_json(value.to_string(), *as_string))
+        },
+        ColumnType::Date { format } => parse_date(trimmed, format.as_deref())
+            .map(Value::String)
+            .ok_or_else(|| match format {
+                Some(f) => format!("cannot convert '{}' to date with format '{}'", raw, f),
+                None => format!("cannot convert '{}' to date", raw),
+            }),
+    }
+}
+
+/// Reads `parser_config.decimals_as_string`, which keeps decimal values of formats
+/// with a decimal type (Avro, Parquet) as exact text instead of numbers.
+pub fn decimals_as_string(config: Option<&Value>) -> bool {
+    config.and_then(|c| c.get("decimals_as_string")).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// Converts exact decimal text to JSON: the text itself with `as_string`, otherwise an
+/// integer when there is no fractional part and it fits in 64 bits, or else a float.
+/// Values beyond the range of a float keep their text.
+pub fn decimal_text_to_json(text: String, as_string: bool) -> Value {
+    if as_string {
+        return Value::String(text);
+    }
+
+    let integer = match text.split_once('.') {
+        Some((integer, fraction)) => fraction.bytes().all(|b| b == b'0').then_some(integer),
+        None => Some(text.as_str()),
+    };
+    if let Some(integer) = integer.and_then(|i| i.parse::<i64>().ok()) {
+        return Value::from(integer);
+    }
+    match text.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+        Some(number) => Value::Number(number),
+        None => Value::String(text),
+    }
+}
+
+/// Converts a big-endian two's complement unscaled decimal (as stored by Parquet
+/// and Avro) into JSON, whatever its width; see [`decimal_text_to_json`].
+pub fn decimal_bytes_to_json(bytes: &[u8], scale: u32, as_string: bool) -> Value {
+    let negative = bytes.first().is_some_and(|b| b & 0x80 != 0);
+    let mut magnitude = bytes.to_vec();
+    if negative {
+        // Two's complement negation: invert, then add one
+        for byte in magnitude.iter_mut() {
+            *byte = !*byte;
+        }
+        for byte in magnitude.iter_mut().rev() {
+            let (sum, carry) = byte.overflowing_add(1);
+            *byte = sum;
+            if !carry {
+                break;
+            }
+        }
+    }
+
+    // Peel off base-10 digits, least significant first
+    let mut digits = Vec::new();
+    while magnitude.iter().any(|&b| b != 0) {
+        let mut remainder = 0u32;
+        for byte in magnitude.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    while digits.len() <= scale as usize {
+        digits.push(b'0');
+    }
+    digits.reverse();
+
+    let digits = String::from_utf8(digits).expect("digits are ASCII");
+    let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+    let sign = if negative { "-" } else { "" };
+    let text = if fraction.is_empty() {
+        format!("{}{}", sign, integer)
+    } else {
+        format!("{}{}.{}", sign, integer, fraction)
+    };
+    decimal_text_to_json(text, as_string)
+}
+
+fn parse_bool(raw: &str) -> Option<bool> {
+    match raw.to_ascii_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn infer_column_type<'a>(values: impl Iterator<Item = &'a str> + Clone) -> ColumnType {
+    let mut values = values.peekable();
+    if values.peek().is_none() {
+        return ColumnType::String;
+    }
+    let all = |check: fn(&str) -> bool| values.clone().all(check);
+
+    if all(|v| v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("false")) {
+        ColumnType::Boolean
+    } else if all(|v| v.trim().parse::<i64>().is_ok()) {
+        ColumnType::Integer
+    } else if all(|v| v.trim().parse::<f64>().is_ok()) {
+        ColumnType::Float
+    } else if all(|v| parse_date(v.trim(), None).is_some()) {
+        ColumnType::Date { format: None }
+    } else {
+        ColumnType::String
+    }
+}
+
+fn string_list(value: &Value, key: &str) -> Result<Vec<String>, IngestionError> {
+    value.as_array()
+        .and_then(|arr| arr.iter().map(|v| v.as_str().map(|s| s.to_string())).collect::<Option<Vec<_>>>())
+        .ok_or_else(|| IngestionError::Config(format!("'{}' must be an array of strings", key)))
+}
@@ -1,67 +1,211 @@
-use csv::ReaderBuilder;
-use std::io::Cursor;
+use csv::{ReaderBuilder, Trim};
+use std::io::{BufRead, BufReader, Cursor, Read};
 use tracing::{debug, info, error};
-use crate::domain::error::IngestionError;
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream, SOURCE_ROW_FIELD}};
+use crate::infrastructure::parsers::{coercion::TypeSchema, encoding::decoding_reader, streaming::spawn_batched};
 
 pub fn parse_csv(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
     parse_csv_with_config(bytes, None)
 }
 
 pub fn parse_csv_with_config(bytes: &[u8], config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
-    let cursor = Cursor::new(bytes);
-    
-    // Check if custom headers are provided in config
-    let custom_headers = config
-        .and_then(|c| c.get("headers"))
-        .and_then(|h| h.as_array())
-        .map(|arr| arr.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect::<Vec<_>>());
-    
-    let has_headers = custom_headers.is_none();
-    debug!("Creating CSV reader with headers={}, custom_headers={:?}", has_headers, custom_headers);
-    
-    let mut reader = ReaderBuilder::new().has_headers(has_headers).from_reader(cursor);
-    
-    let headers = if let Some(custom) = custom_headers {
-        custom
-    } else {
-        reader.headers()
+    let mut documents = Vec::new();
+    read_csv_records(Cursor::new(bytes), config, &mut |doc| {
+        documents.push(doc);
+        Ok(())
+    })?;
+    Ok(documents)
+}
+
+/// Parses CSV incrementally from `stream`, yielding documents in batches of `batch_size`.
+pub fn stream_csv_with_config(stream: FileStream, config: Option<serde_json::Value>, batch_size: usize) -> DocumentBatchStream {
+    spawn_batched(stream, batch_size, move |reader, emit| {
+        read_csv_records(reader, config.as_ref(), emit)
+    })
+}
+
+/// Reader options taken from a rule's `parser_config`.
+#[derive(Debug, Clone)]
+struct CsvOptions {
+    custom_headers: Option<Vec<String>>,
+    has_headers: bool,
+    delimiter: u8,
+    quote: u8,
+    escape: Option<u8>,
+    double_quote: bool,
+    comment: Option<u8>,
+    skip_rows: usize,
+    trim: Trim,
+    flexible: bool,
+}
+
+impl CsvOptions {
+    fn from_config(config: Option<&serde_json::Value>) -> Result<Self, IngestionError> {
+        let get = |key: &str| config.and_then(|c| c.get(key));
+
+        // Check if custom headers are provided in config
+        let custom_headers = get("headers")
+            .and_then(|h| h.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect::<Vec<_>>());
+
+        // Custom headers replace the header row unless the file is declared to have one
+        let has_headers = match get("has_headers") {
+            Some(v) => v.as_bool().ok_or_else(|| IngestionError::Config("'has_headers' must be a boolean".to_string()))?,
+            None => custom_headers.is_none(),
+        };
+
+        let trim = match get("trim") {
+            None | Some(serde_json::Value::Bool(false)) => Trim::None,
+            Some(serde_json::Value::Bool(true)) => Trim::All,
+            Some(serde_json::Value::String(mode)) => match mode.as_str() {
+                "none" => Trim::None,
+                "all" => Trim::All,
+                "headers" => Trim::Headers,
+                "fields" => Trim::Fields,
+                other => return Err(IngestionError::Config(format!("Unknown CSV trim mode '{}'", other))),
+            },
+            Some(_) => return Err(IngestionError::Config("'trim' must be a boolean or one of none, all, headers, fields".to_string())),
+        };
+
+        Ok(Self {
+            custom_headers,
+            has_headers,
+            delimiter: single_byte(get("delimiter"), "delimiter")?.unwrap_or(b','),
+            quote: single_byte(get("quote"), "quote")?.unwrap_or(b'"'),
+            escape: single_byte(get("escape"), "escape")?,
+            double_quote: get("double_quote").and_then(|v| v.as_bool()).unwrap_or(true),
+            comment: single_byte(get("comment"), "comment")?,
+            skip_rows: get("skip_rows").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+            trim,
+            flexible: get("flexible").and_then(|v| v.as_bool()).unwrap_or(false),
+        })
+    }
+
+    fn reader_builder(&self) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .has_headers(self.has_headers)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.double_quote)
+            .comment(self.comment)
+            .trim(self.trim)
+            .flexible(self.flexible);
+        builder
+    }
+}
+
+fn single_byte(value: Option<&serde_json::Value>, key: &str) -> Result<Option<u8>, IngestionError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let s = value.as_str()
+        .ok_or_else(|| IngestionError::Config(format!("'{}' must be a string", key)))?;
+    let s = if s == "tab" { "\t" } else { s };
+    match s.as_bytes() {
+        [b] if b.is_ascii() => Ok(Some(*b)),
+        _ => Err(IngestionError::Config(format!("'{}' must be a single ASCII character, got '{}'", key, s))),
+    }
+}
+
+fn read_csv_records<R: Read>(
+    source: R,
+    config: Option<&serde_json::Value>,
+    emit: &mut dyn FnMut(serde_json::Value) -> Result<(), IngestionError>,
+) -> Result<(), IngestionError> {
+    let options = CsvOptions::from_config(config)?;
+    debug!("Creating CSV reader with options: {:?}", options);
+
+    // Drop preamble lines (titles, export banners) before the CSV content starts
+    let mut source = BufReader::new(decoding_reader(source, config)?);
+    let mut line = Vec::new();
+    let mut skipped_lines = 0;
+    for skipped in 0..options.skip_rows {
+        line.clear();
+        let read = source.read_until(b'\n', &mut line).map_err(|e| {
+            error!("Failed to skip CSV row {}: {}", skipped + 1, e);
+            IngestionError::Parse(e.to_string())
+        })?;
+        if read == 0 {
+            break;
+        }
+        skipped_lines += 1;
+    }
+
+    let mut reader = options.reader_builder().from_reader(source);
+
+    let headers = if options.has_headers {
+        let file_headers: Vec<String> = reader.headers()
             .map_err(|e| {
                 error!("Failed to read CSV headers: {}", e);
                 IngestionError::Parse(e.to_string())
             })?
             .iter()
             .map(|s| s.to_string())
-            .collect()
+            .collect();
+        options.custom_headers.clone().unwrap_or(file_headers)
+    } else {
+        options.custom_headers.clone().unwrap_or_default()
     };
-    
+
     debug!("CSV headers: {:?}", headers);
     info!("Found {} columns in CSV", headers.len());
-    
-    let mut documents = Vec::new();
+
+    let mut schema = TypeSchema::from_config(config)?;
+    let mut records = reader.into_records();
+
+    // Buffer a bounded sample of rows so column types can be inferred before emitting
+    let mut sample = Vec::new();
+    if schema.infer_types() {
+        while sample.len() < schema.sample_rows() {
+            match records.next() {
+                Some(record) => sample.push(record.map_err(|e| {
+                    error!("Failed to read CSV record at row {}: {}", sample.len() + 1, e);
+                    IngestionError::Parse(e.to_string())
+                })?),
+                None => break,
+            }
+        }
+        let sample_values: Vec<Vec<String>> = sample.iter()
+            .map(|record| record.iter().map(|s| s.to_string()).collect())
+            .collect();
+        schema.infer(&headers, &sample_values);
+        debug!("Inferred CSV column types from {} sample rows", sample.len());
+    }
+
     let mut row_count = 0;
-    
-    for record in reader.records() {
+
+    for record in sample.into_iter().map(Ok).chain(records) {
         let record = record.map_err(|e| {
             error!("Failed to read CSV record at row {}: {}", row_count + 1, e);
             IngestionError::Parse(e.to_string())
         })?;
-        
+
         row_count += 1;
         let mut doc = serde_json::Map::new();
-        
+
         for (i, field) in record.iter().enumerate() {
             let fallback = format!("column_{}", i);
             let header = headers.get(i).map(|s| s.as_str()).unwrap_or(&fallback);
-            doc.insert(header.to_string(), serde_json::Value::String(field.to_string()));
+            let value = schema.convert(header, field).map_err(|e| {
+                error!("Failed to convert CSV row {} column '{}': {}", row_count, header, e);
+                IngestionError::Parse(format!("CSV row {}, column '{}': {}", row_count, header, e))
+            })?;
+            doc.insert(header.to_string(), value);
+        }
+        // Quoted fields may span lines, so the reader's position is the only reliable line number
+        if let Some(position) = record.position() {
+            doc.insert(SOURCE_ROW_FIELD.to_string(), serde_json::Value::from(position.line() + skipped_lines));
         }
-        
-        documents.push(serde_json::Value::Object(doc));
-        
+
+        emit(serde_json::Value::Object(doc))?;
+
         if row_count % 1000 == 0 {
             debug!("Processed {} CSV rows", row_count);
         }
     }
-    
+
     info!("Parsed {} rows from CSV", row_count);
-    Ok(documents)
-}
\ No newline at end of file
+    Ok(())
+}
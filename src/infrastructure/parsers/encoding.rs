@@ -0,0 +1,188 @@
+use std::borrow::Cow;
+use std::io::Read;
+use encoding_rs::{Decoder, DecoderResult, Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde_json::Value;
+use tracing::{debug, error};
+use crate::domain::error::IngestionError;
+
+/// Bytes inspected when auto-detecting an encoding.
+const DETECT_BYTES: usize = 8192;
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Reads `parser_config.encoding`; `None` means auto-detect.
+///
+/// Labels follow the WHATWG Encoding Standard, so `latin1` and `iso-8859-1` decode as
+/// Windows-1252, its superset.
+fn encoding_from_config(config: Option<&Value>) -> Result<Option<&'static Encoding>, IngestionError> {
+    match config.and_then(|c| c.get("encoding")) {
+        None => Ok(None),
+        Some(Value::String(label)) if label.eq_ignore_ascii_case("auto") => Ok(None),
+        Some(Value::String(label)) => Encoding::for_label(label.trim().as_bytes())
+            .map(Some)
+            .ok_or_else(|| IngestionError::Config(format!("Unknown encoding '{}'", label))),
+        Some(_) => Err(IngestionError::Config("'encoding' must be a string".to_string())),
+    }
+}
+
+/// Guesses the encoding of `head` from its BOM, UTF-16 zero-byte patterns, and UTF-8 validity.
+pub fn detect_encoding(head: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(head) {
+        return encoding;
+    }
+
+    // ASCII text in UTF-16 has a zero in every other byte
+    let sample = &head[..head.len().min(512) & !1];
+    if !sample.is_empty() {
+        let pairs = sample.len() / 2;
+        let even_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd_zeros = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        if odd_zeros * 2 > pairs && even_zeros * 8 < pairs {
+            return UTF_16LE;
+        }
+        if even_zeros * 2 > pairs && odd_zeros * 8 < pairs {
+            return UTF_16BE;
+        }
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => UTF_8,
+        // A multi-byte character cut off at the end of a full sample
+        Err(e) if e.error_len().is_none() && head.len() >= DETECT_BYTES => UTF_8,
+        Err(_) => WINDOWS_1252,
+    }
+}
+
+/// Wraps `source` so that it yields UTF-8 without a BOM.
+///
+/// Reading fails with the byte offset of the first sequence that is invalid in the chosen
+/// encoding, wherever it occurs in the content.
+pub fn decoding_reader<R: Read>(mut source: R, config: Option<&Value>) -> Result<impl Read, IngestionError> {
+    let mut head = Vec::with_capacity(DETECT_BYTES);
+    (&mut source).take(DETECT_BYTES as u64).read_to_end(&mut head).map_err(|e| {
+        error!("Failed to read content for encoding detection: {}", e);
+        IngestionError::Parse(e.to_string())
+    })?;
+
+    // A BOM always wins over the configured encoding and is stripped
+    let (encoding, bom_length) = match (Encoding::for_bom(&head), encoding_from_config(config)?) {
+        (Some((encoding, bom_length)), _) => (encoding, bom_length),
+        (None, Some(encoding)) => (encoding, 0),
+        (None, None) => (detect_encoding(&head), 0),
+    };
+    debug!("Decoding content as {}", encoding.name());
+
+    Ok(StrictDecoder {
+        source,
+        encoding,
+        decoder: encoding.new_decoder_without_bom_handling(),
+        input: head,
+        start: bom_length,
+        offset: bom_length,
+        eof: false,
+        finished: false,
+        output: Vec::new(),
+        output_start: 0,
+    })
+}
+
+/// Decodes a byte stream to UTF-8, failing on malformed input instead of replacing it.
+struct StrictDecoder<R> {
+    source: R,
+    encoding: &'static Encoding,
+    decoder: Decoder,
+    /// Undecoded bytes from `start` on.
+    input: Vec<u8>,
+    start: usize,
+    /// Source offset of `input[start]`, for error messages.
+    offset: usize,
+    eof: bool,
+    finished: bool,
+    /// Decoded bytes not yet handed out, from `output_start` on.
+    output: Vec<u8>,
+    output_start: usize,
+}
+
+impl<R: Read> Read for StrictDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.output_start < self.output.len() {
+                let count = buf.len().min(self.output.len() - self.output_start);
+                buf[..count].copy_from_slice(&self.output[self.output_start..self.output_start + count]);
+                self.output_start += count;
+                return Ok(count);
+            }
+            if self.finished || buf.is_empty() {
+                return Ok(0);
+            }
+
+            if self.start == self.input.len() && !self.eof {
+                self.input.resize(DETECT_BYTES, 0);
+                let read = self.source.read(&mut self.input)?;
+                self.input.truncate(read);
+                self.start = 0;
+                self.eof = read == 0;
+            }
+
+            let pending = &self.input[self.start..];
+            let capacity = self.decoder.max_utf8_buffer_length_without_replacement(pending.len()).unwrap_or(pending.len() * 3);
+            let mut decoded = String::with_capacity(capacity);
+            let (result, read) = self.decoder.decode_to_string_without_replacement(pending, &mut decoded, self.eof);
+            if let DecoderResult::Malformed(malformed, consumed_after) = result {
+                let offset = self.offset + read - malformed as usize - consumed_after as usize;
+                error!("Content contains a byte sequence that is invalid in {} at offset {}", self.encoding.name(), offset);
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid {} byte sequence at offset {}", self.encoding.name(), offset),
+                ));
+            }
+            self.start += read;
+            self.offset += read;
+            self.finished = self.eof && result == DecoderResult::InputEmpty;
+            self.output = decoded.into_bytes();
+            self.output_start = 0;
+        }
+    }
+}
+
+/// Decodes `bytes` to UTF-8 without a BOM, borrowing when they already are.
+///
+/// Fails with the byte offset of the first sequence that is invalid in the chosen encoding.
+pub fn decode_bytes<'a>(bytes: &'a [u8], config: Option<&Value>) -> Result<Cow<'a, str>, IngestionError> {
+    let configured = encoding_from_config(config)?;
+
+    // A BOM always wins over the configured encoding and is stripped
+    let (encoding, bom_length) = match (Encoding::for_bom(bytes), configured) {
+        (Some((encoding, bom_length)), _) => (encoding, bom_length),
+        (None, Some(encoding)) => (encoding, 0),
+        (None, None) => (detect_encoding(&bytes[..bytes.len().min(DETECT_BYTES)]), 0),
+    };
+    debug!("Decoding content as {}", encoding.name());
+
+    let body = &bytes[bom_length..];
+    encoding.decode_without_bom_handling_and_without_replacement(body).ok_or_else(|| {
+        let offset = bom_length + invalid_offset(encoding, body);
+        error!("Content contains a byte sequence that is invalid in {} at offset {}", encoding.name(), offset);
+        IngestionError::Parse(format!("Invalid {} byte sequence at offset {}", encoding.name(), offset))
+    })
+}
+
+/// Offset of the first malformed sequence in `bytes`, or its length when there is none.
+fn invalid_offset(encoding: &'static Encoding, bytes: &[u8]) -> usize {
+    if encoding == UTF_8 {
+        return std::str::from_utf8(bytes).err().map_or(bytes.len(), |e| e.valid_up_to());
+    }
+
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let capacity = decoder.max_utf8_buffer_length_without_replacement(bytes.len()).unwrap_or(bytes.len() * 3);
+    let mut decoded = String::with_capacity(capacity);
+    match decoder.decode_to_string_without_replacement(bytes, &mut decoded, true) {
+        (DecoderResult::Malformed(malformed, consumed_after), read) => read - malformed as usize - consumed_after as usize,
+        (_, read) => read,
+    }
+}
+
+/// Drops a leading UTF-8 byte order mark.
+pub fn strip_bom(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes)
+}
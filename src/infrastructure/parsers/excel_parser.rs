@@ -1,46 +1,173 @@
-use calamine::{Reader, Xlsx};
+use calamine::{open_workbook_auto_from_rs, Data, Range, Reader};
 use std::io::Cursor;
 use tracing::{debug, info, error};
-use crate::domain::error::IngestionError;
+use crate::domain::{error::IngestionError, ports::SOURCE_ROW_FIELD};
+
+/// Largest integer an f64 represents exactly, used to emit whole numbers as integers.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+#[derive(Debug, Clone)]
+enum SheetSelection {
+    Index(usize),
+    Name(String),
+    All,
+}
 
 pub fn parse_excel(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
-    debug!("Parsing Excel file");
-    let cursor = Cursor::new(bytes);
-    let mut workbook: Xlsx<_> = Xlsx::new(cursor)
+    parse_excel_with_config(bytes, None)
+}
+
+pub fn parse_excel_with_config(bytes: &[u8], config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
+    debug!("Parsing Excel file with config: {:?}", config);
+    let selection = sheet_selection(config)?;
+    let header_row = config
+        .and_then(|c| c.get("header_row"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+
+    // Detects xls, xlsx, xlsb and ods from the content rather than the extension
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes))
         .map_err(|e| {
             error!("Failed to open Excel file: {}", e);
             IngestionError::Parse(e.to_string())
         })?;
-    
-    let mut documents = Vec::new();
-    if let Some(Ok(range)) = workbook.worksheet_range_at(0) {
-        debug!("Processing Excel worksheet");
-        let mut rows = range.rows();
-        let headers: Vec<String> = if let Some(header_row) = rows.next() {
-            header_row.iter().map(|cell| cell.to_string()).collect()
-        } else {
-            debug!("No header row found in Excel file");
-            return Ok(documents);
-        };
-
-        debug!("Excel headers: {:?}", headers);
-        let mut row_count = 0;
-
-        for row in rows {
-            let mut doc = serde_json::Map::new();
-            for (i, cell) in row.iter().enumerate() {
-                if let Some(header) = headers.get(i) {
-                    doc.insert(header.clone(), serde_json::Value::String(cell.to_string()));
-                }
+
+    let sheet_names = workbook.sheet_names();
+    debug!("Workbook sheets: {:?}", sheet_names);
+
+    let targets: Vec<String> = match &selection {
+        SheetSelection::All => sheet_names.clone(),
+        SheetSelection::Name(name) => {
+            if !sheet_names.contains(name) {
+                error!("Sheet '{}' not found in workbook", name);
+                return Err(IngestionError::Parse(format!("Sheet '{}' not found, available sheets: {:?}", name, sheet_names)));
             }
-            documents.push(serde_json::Value::Object(doc));
-            row_count += 1;
-        }
-        
-        info!("Parsed {} rows from Excel file", row_count);
-    } else {
-        debug!("No worksheet found in Excel file");
+            vec![name.clone()]
+        },
+        SheetSelection::Index(index) => match sheet_names.get(*index) {
+            Some(name) => vec![name.clone()],
+            None => {
+                error!("No worksheet at index {} in workbook", index);
+                return Err(IngestionError::Parse(format!("No sheet at index {}, available sheets: {:?}", index, sheet_names)));
+            }
+        },
+    };
+
+    let tag_sheet = matches!(selection, SheetSelection::All);
+    let mut documents = Vec::new();
+
+    for sheet in targets {
+        debug!("Processing Excel worksheet '{}'", sheet);
+        let range = workbook.worksheet_range(&sheet)
+            .map_err(|e| {
+                error!("Failed to read worksheet '{}': {}", sheet, e);
+                IngestionError::Parse(e.to_string())
+            })?;
+
+        let before = documents.len();
+        read_sheet(&range, header_row, tag_sheet.then_some(sheet.as_str()), &mut documents);
+        info!("Parsed {} rows from Excel worksheet '{}'", documents.len() - before, sheet);
     }
-    
+
     Ok(documents)
-}
\ No newline at end of file
+}
+
+fn sheet_selection(config: Option<&serde_json::Value>) -> Result<SheetSelection, IngestionError> {
+    let Some(config) = config else {
+        return Ok(SheetSelection::Index(0));
+    };
+
+    if config.get("all_sheets").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Ok(SheetSelection::All);
+    }
+    if let Some(name) = config.get("sheet_name") {
+        let name = name.as_str()
+            .ok_or_else(|| IngestionError::Config("'sheet_name' must be a string".to_string()))?;
+        return Ok(SheetSelection::Name(name.to_string()));
+    }
+    if let Some(index) = config.get("sheet_index") {
+        let index = index.as_u64()
+            .ok_or_else(|| IngestionError::Config("'sheet_index' must be a non-negative integer".to_string()))?;
+        return Ok(SheetSelection::Index(index as usize));
+    }
+    Ok(SheetSelection::Index(0))
+}
+
+fn read_sheet(range: &Range<Data>, header_row: u32, sheet_name: Option<&str>, documents: &mut Vec<serde_json::Value>) {
+    // `header_row` is a worksheet row, while the range starts at the first used cell
+    let first_row = range.start().map(|(row, _)| row).unwrap_or(0);
+    let skip = header_row.saturating_sub(first_row) as usize;
+    let mut rows = range.rows().skip(skip);
+
+    let headers: Vec<String> = if let Some(header_row) = rows.next() {
+        header_row.iter()
+            .enumerate()
+            .map(|(i, cell)| match cell {
+                Data::Empty => format!("column_{}", i),
+                other => other.to_string(),
+            })
+            .collect()
+    } else {
+        debug!("No header row found in Excel worksheet");
+        return;
+    };
+
+    debug!("Excel headers: {:?}", headers);
+
+    // 1-based worksheet row of the first data row, right below the header
+    let first_data_row = first_row as usize + skip + 2;
+    for (i, row) in rows.enumerate() {
+        if row.iter().all(|cell| matches!(cell, Data::Empty)) {
+            continue;
+        }
+        let mut doc = serde_json::Map::new();
+        for (i, cell) in row.iter().enumerate() {
+            let fallback = format!("column_{}", i);
+            let header = headers.get(i).unwrap_or(&fallback);
+            doc.insert(header.clone(), cell_to_json(cell));
+        }
+        if let Some(name) = sheet_name {
+            doc.insert("sheet_name".to_string(), serde_json::Value::String(name.to_string()));
+        }
+        doc.insert(SOURCE_ROW_FIELD.to_string(), serde_json::Value::from(first_data_row + i));
+        documents.push(serde_json::Value::Object(doc));
+    }
+}
+
+fn cell_to_json(cell: &Data) -> serde_json::Value {
+    match cell {
+        Data::Int(i) => serde_json::Value::from(*i),
+        Data::Float(f) => {
+            if f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER {
+                serde_json::Value::from(*f as i64)
+            } else {
+                serde_json::Number::from_f64(*f)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null)
+            }
+        },
+        Data::String(s) => serde_json::Value::String(s.clone()),
+        Data::Bool(b) => serde_json::Value::Bool(*b),
+        Data::DateTime(dt) => {
+            if dt.is_duration() {
+                dt.as_duration()
+                    .map(|d| serde_json::Value::from(d.num_seconds()))
+                    .unwrap_or(serde_json::Value::Null)
+            } else {
+                dt.as_datetime()
+                    .map(|d| {
+                        if d.time() == chrono::NaiveTime::MIN {
+                            d.format("%Y-%m-%d").to_string()
+                        } else {
+                            d.format("%Y-%m-%dT%H:%M:%S").to_string()
+                        }
+                    })
+                    .map(serde_json::Value::String)
+                    .unwrap_or(serde_json::Value::Null)
+            }
+        },
+        Data::DateTimeIso(s) | Data::DurationIso(s) => serde_json::Value::String(s.clone()),
+        Data::Error(e) => serde_json::Value::String(e.to_string()),
+        Data::Empty => serde_json::Value::Null,
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::{BufRead, BufReader, Cursor, Read};
+use serde_json::{Map, Value};
+use tracing::{debug, info, error};
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream, SOURCE_ROW_FIELD}};
+use crate::infrastructure::parsers::{coercion::{coerce, ColumnSpec}, encoding::decoding_reader, streaming::spawn_batched};
+
+/// Which padding to remove from a field before conversion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Trim {
+    Both,
+    Left,
+    Right,
+    None,
+}
+
+impl Trim {
+    fn apply(self, raw: &str) -> &str {
+        match self {
+            Trim::Both => raw.trim(),
+            Trim::Left => raw.trim_start(),
+            Trim::Right => raw.trim_end(),
+            Trim::None => raw,
+        }
+    }
+}
+
+/// A field at a fixed character range of each line.
+#[derive(Debug, Clone)]
+struct FieldSpec {
+    name: String,
+    /// Zero-based character offset.
+    start: usize,
+    length: usize,
+    trim: Trim,
+    /// Fields without a `type` stay strings.
+    spec: Option<ColumnSpec>,
+}
+
+impl FieldSpec {
+    /// `start` and `end` are 1-based and inclusive, as in record layouts and copybooks.
+    fn from_config(index: usize, value: &Value) -> Result<Self, IngestionError> {
+        let field = value.as_object()
+            .ok_or_else(|| IngestionError::Config(format!("fields[{}] must be an object", index)))?;
+        let name = field.get("name").and_then(|v| v.as_str())
+            .ok_or_else(|| IngestionError::Config(format!("fields[{}] is missing a string 'name'", index)))?
+            .to_string();
+        let position = |key: &str| field.get(key).and_then(|v| v.as_u64()).map(|v| v as usize);
+
+        let start = position("start")
+            .filter(|&start| start >= 1)
+            .ok_or_else(|| IngestionError::Config(format!("Field '{}' needs a 'start' of at least 1", name)))?;
+        let length = match (position("length"), position("end")) {
+            (Some(length), None) if length > 0 => length,
+            (None, Some(end)) if end >= start => end - start + 1,
+            (Some(_), Some(_)) => return Err(IngestionError::Config(format!("Field '{}' must set either 'length' or 'end', not both", name))),
+            _ => return Err(IngestionError::Config(format!("Field '{}' needs a positive 'length' or an 'end' at or after 'start'", name))),
+        };
+
+        let trim = match field.get("trim") {
+            None => Trim::Both,
+            Some(Value::Bool(true)) => Trim::Both,
+            Some(Value::Bool(false)) => Trim::None,
+            Some(Value::String(s)) => match s.as_str() {
+                "both" => Trim::Both,
+                "left" => Trim::Left,
+                "right" => Trim::Right,
+                "none" => Trim::None,
+                other => return Err(IngestionError::Config(format!("Field '{}' has unknown trim '{}', expected both, left, right or none", name, other))),
+            },
+            Some(_) => return Err(IngestionError::Config(format!("Field '{}' trim must be a boolean or a string", name))),
+        };
+
+        let spec = match field.get("type") {
+            Some(_) => Some(ColumnSpec::from_config(&name, value)?),
+            None => None,
+        };
+
+        Ok(Self { name, start: start - 1, length, trim, spec })
+    }
+}
+
+struct FixedWidthOptions {
+    fields: Vec<FieldSpec>,
+    skip_rows: usize,
+    null_values: Vec<String>,
+}
+
+impl FixedWidthOptions {
+    fn from_config(config: Option<&Value>) -> Result<Self, IngestionError> {
+        let fields = config.and_then(|c| c.get("fields"))
+            .ok_or_else(|| IngestionError::Config("Fixed-width parsing needs 'fields' in parser_config".to_string()))?
+            .as_array()
+            .ok_or_else(|| IngestionError::Config("'fields' must be an array of field specs".to_string()))?
+            .iter()
+            .enumerate()
+            .map(|(i, field)| FieldSpec::from_config(i, field))
+            .collect::<Result<Vec<_>, _>>()?;
+        if fields.is_empty() {
+            return Err(IngestionError::Config("'fields' must not be empty".to_string()));
+        }
+
+        let skip_rows = config.and_then(|c| c.get("skip_rows")).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let null_values = match config.and_then(|c| c.get("null_values")) {
+            Some(Value::Array(values)) => values.iter()
+                .map(|v| v.as_str().map(|s| s.to_string()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| IngestionError::Config("'null_values' must be an array of strings".to_string()))?,
+            Some(_) => return Err(IngestionError::Config("'null_values' must be an array of strings".to_string())),
+            None => vec![String::new()],
+        };
+
+        Ok(Self { fields, skip_rows, null_values })
+    }
+}
+
+/// True when `parser_config` describes fixed-width fields.
+pub fn has_fixed_width_fields(config: Option<&Value>) -> bool {
+    config.and_then(|c| c.get("fields")).is_some()
+}
+
+pub fn parse_fixed_width_with_config(bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+    let mut documents = Vec::new();
+    read_fixed_width_records(Cursor::new(bytes), config, &mut |doc| {
+        documents.push(doc);
+        Ok(())
+    })?;
+    Ok(documents)
+}
+
+/// Parses fixed-width lines incrementally from `stream`, yielding documents in batches of `batch_size`.
+pub fn stream_fixed_width_with_config(stream: FileStream, config: Option<Value>, batch_size: usize) -> DocumentBatchStream {
+    spawn_batched(stream, batch_size, move |reader, emit| {
+        read_fixed_width_records(reader, config.as_ref(), emit)
+    })
+}
+
+fn read_fixed_width_records<R: Read>(
+    source: R,
+    config: Option<&Value>,
+    emit: &mut dyn FnMut(Value) -> Result<(), IngestionError>,
+) -> Result<(), IngestionError> {
+    let options = FixedWidthOptions::from_config(config)?;
+    debug!("Parsing fixed-width content with {} fields, skipping {} rows", options.fields.len(), options.skip_rows);
+
+    let reader = BufReader::new(decoding_reader(source, config)?);
+    let mut record_count = 0;
+
+    for (i, line) in reader.lines().enumerate().skip(options.skip_rows) {
+        let line_number = i + 1;
+        let line = line.map_err(|e| {
+            error!("Failed to read fixed-width line {}: {}", line_number, e);
+            IngestionError::Parse(format!("Fixed-width line {}: {}", line_number, e))
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        emit(parse_line(&line, line_number, &options)?)?;
+        record_count += 1;
+    }
+
+    info!("Parsed {} fixed-width records", record_count);
+    Ok(())
+}
+
+fn parse_line(line: &str, line_number: usize, options: &FixedWidthOptions) -> Result<Value, IngestionError> {
+    // Positions count characters, not bytes
+    let chars: Vec<char> = line.chars().collect();
+    let mut doc = Map::new();
+
+    for field in &options.fields {
+        let raw: String = chars.iter().skip(field.start).take(field.length).collect();
+        let raw = field.trim.apply(&raw);
+
+        let value = match &field.spec {
+            None => Value::String(raw.to_string()),
+            Some(spec) => {
+                let null_values = spec.null_values.as_ref().unwrap_or(&options.null_values);
+                if null_values.iter().any(|n| n == raw) {
+                    Value::Null
+                } else {
+                    coerce(raw, spec).map_err(|e| {
+                        error!("Fixed-width line {}, field '{}': {}", line_number, field.name, e);
+                        IngestionError::Parse(format!("Fixed-width line {}, field '{}': {}", line_number, field.name, e))
+                    })?
+                }
+            },
+        };
+        doc.insert(field.name.clone(), value);
+    }
+    doc.insert(SOURCE_ROW_FIELD.to_string(), Value::from(line_number));
+
+    Ok(Value::Object(doc))
+}
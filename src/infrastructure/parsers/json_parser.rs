@@ -1,27 +1,133 @@
-use tracing::{debug, error};
+use tracing::{debug, warn, error};
 use crate::domain::error::IngestionError;
+use crate::infrastructure::parsers::encoding::decode_bytes;
 
 pub fn parse_json(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
+    parse_json_with_config(bytes, None)
+}
+
+pub fn parse_json_with_config(bytes: &[u8], config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
     debug!("Parsing JSON content");
-    let value: serde_json::Value = serde_json::from_slice(bytes)
+    let content = decode_bytes(bytes, config)?;
+    let mut value: serde_json::Value = serde_json::from_str(&content)
         .map_err(|e| {
             error!("Failed to parse JSON: {}", e);
-            debug!("JSON content preview: {}", 
-                String::from_utf8_lossy(&bytes[..std::cmp::min(200, bytes.len())])
+            debug!("JSON content preview: {}",
+                content.chars().take(200).collect::<String>()
             );
             IngestionError::Parse(e.to_string())
         })?;
-    
-    let result = match value {
+
+    let records_path = config
+        .and_then(|c| c.get("records_path"))
+        .map(|p| p.as_str().ok_or_else(|| IngestionError::Config("'records_path' must be a string".to_string())))
+        .transpose()?;
+    let metadata = metadata_fields(&value, config)?;
+
+    let records = match records_path {
+        Some(path) => {
+            let pointer = to_json_pointer(path);
+            debug!("Selecting JSON records at '{}' (pointer '{}')", path, pointer);
+            // Metadata was already copied out, so the records can be moved out of the document
+            let selected = value.pointer_mut(&pointer)
+                .ok_or_else(|| {
+                    error!("records_path '{}' not found in JSON document", path);
+                    IngestionError::Parse(format!("records_path '{}' not found in JSON document", path))
+                })?
+                .take();
+            explode(selected)
+        },
+        None => explode(value),
+    };
+
+    if metadata.is_empty() {
+        return Ok(records);
+    }
+
+    debug!("Copying {} metadata fields into each record", metadata.len());
+    let mut kept = 0;
+    let records = records
+        .into_iter()
+        .map(|mut record| {
+            match record {
+                serde_json::Value::Object(ref mut map) => {
+                    // A record's own field wins over a metadata field of the same name
+                    for (key, value) in &metadata {
+                        if map.contains_key(key) {
+                            kept += 1;
+                        } else {
+                            map.insert(key.clone(), value.clone());
+                        }
+                    }
+                },
+                _ => warn!("Cannot copy metadata fields into non-object JSON record"),
+            }
+            record
+        })
+        .collect();
+    if kept > 0 {
+        warn!("Kept {} record fields that share their name with a metadata field instead of overwriting them", kept);
+    }
+    Ok(records)
+}
+
+fn explode(value: serde_json::Value) -> Vec<serde_json::Value> {
+    match value {
         serde_json::Value::Array(arr) => {
             debug!("JSON contains array with {} elements", arr.len());
-            Ok(arr)
+            arr
         },
         single => {
             debug!("JSON contains single object, wrapping in array");
-            Ok(vec![single])
+            vec![single]
         }
+    }
+}
+
+/// Resolves `metadata_fields`, given either as a list of paths (named after their
+/// last segment) or as an object of output name to path.
+fn metadata_fields(root: &serde_json::Value, config: Option<&serde_json::Value>) -> Result<Vec<(String, serde_json::Value)>, IngestionError> {
+    let Some(fields) = config.and_then(|c| c.get("metadata_fields")) else {
+        return Ok(Vec::new());
+    };
+
+    let named: Vec<(String, &str)> = match fields {
+        serde_json::Value::Array(paths) => paths.iter()
+            .map(|p| {
+                let path = p.as_str()
+                    .ok_or_else(|| IngestionError::Config("'metadata_fields' entries must be strings".to_string()))?;
+                let name = path.trim_start_matches('/').rsplit(['/', '.']).next().unwrap_or(path);
+                Ok((name.to_string(), path))
+            })
+            .collect::<Result<_, IngestionError>>()?,
+        serde_json::Value::Object(map) => map.iter()
+            .map(|(name, p)| {
+                let path = p.as_str()
+                    .ok_or_else(|| IngestionError::Config(format!("'metadata_fields.{}' must be a string path", name)))?;
+                Ok((name.clone(), path))
+            })
+            .collect::<Result<_, IngestionError>>()?,
+        _ => return Err(IngestionError::Config("'metadata_fields' must be an array of paths or an object".to_string())),
     };
-    
-    result
-}
\ No newline at end of file
+
+    Ok(named
+        .into_iter()
+        .map(|(name, path)| {
+            let value = root.pointer(&to_json_pointer(path)).cloned().unwrap_or_else(|| {
+                warn!("Metadata field '{}' not found in JSON document", path);
+                serde_json::Value::Null
+            });
+            (name, value)
+        })
+        .collect())
+}
+
+/// Accepts a JSON Pointer (`/data/items`) or a dotted path (`data.items`).
+fn to_json_pointer(path: &str) -> String {
+    if path.is_empty() || path.starts_with('/') {
+        return path.to_string();
+    }
+    path.split('.')
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
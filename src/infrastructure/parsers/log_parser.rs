@@ -0,0 +1,171 @@
+use std::io::{BufRead, BufReader, Cursor, Read};
+use regex::Regex;
+use serde_json::{Map, Value};
+use tracing::{debug, info, warn, error};
+use crate::domain::{error::IngestionError, ports::SOURCE_ROW_FIELD};
+use crate::infrastructure::parsers::{coercion::TypeSchema, encoding::decoding_reader};
+
+const DEFAULT_CONTINUATION_FIELD: &str = "continuation";
+
+/// What to do with a line that neither starts an entry nor matches the continuation pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OnUnmatched {
+    /// Append it to the previous entry, like a continuation line.
+    Attach,
+    /// Drop it and report the line numbers.
+    Skip,
+    Fail,
+}
+
+struct LogOptions {
+    line_pattern: Regex,
+    continuation_pattern: Option<Regex>,
+    continuation_field: String,
+    on_unmatched: OnUnmatched,
+    schema: TypeSchema,
+}
+
+impl LogOptions {
+    fn from_config(config: Option<&Value>) -> Result<Self, IngestionError> {
+        let option_str = |key: &str| config.and_then(|c| c.get(key)).and_then(|v| v.as_str());
+        let compile = |key: &str, pattern: &str| Regex::new(pattern).map_err(|e| {
+            error!("Invalid {} '{}': {}", key, pattern, e);
+            IngestionError::Config(format!("Invalid {}: {}", key, e))
+        });
+
+        let line_pattern = option_str("line_pattern")
+            .ok_or_else(|| IngestionError::Config("Log parsing needs a 'line_pattern' in parser_config".to_string()))?;
+        let line_pattern = compile("line_pattern", line_pattern)?;
+        if line_pattern.capture_names().flatten().next().is_none() {
+            return Err(IngestionError::Config("'line_pattern' must contain at least one named capture group".to_string()));
+        }
+
+        let continuation_pattern = option_str("continuation_pattern")
+            .map(|pattern| compile("continuation_pattern", pattern))
+            .transpose()?;
+
+        let on_unmatched = match option_str("on_unmatched") {
+            None | Some("attach") => OnUnmatched::Attach,
+            Some("skip") => OnUnmatched::Skip,
+            Some("fail") => OnUnmatched::Fail,
+            Some(other) => return Err(IngestionError::Config(format!("Unknown on_unmatched mode '{}', expected attach, skip or fail", other))),
+        };
+
+        Ok(Self {
+            line_pattern,
+            continuation_pattern,
+            continuation_field: option_str("continuation_field").unwrap_or(DEFAULT_CONTINUATION_FIELD).to_string(),
+            on_unmatched,
+            schema: TypeSchema::from_config(config)?,
+        })
+    }
+}
+
+/// True when `parser_config` describes log lines.
+pub fn has_line_pattern(config: Option<&Value>) -> bool {
+    config.and_then(|c| c.get("line_pattern")).is_some()
+}
+
+pub fn parse_log_with_config(bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+    let mut documents = Vec::new();
+    read_log_records(Cursor::new(bytes), config, &mut |doc| {
+        documents.push(doc);
+        Ok(())
+    })?;
+    Ok(documents)
+}
+
+/// An entry whose continuation lines may still follow.
+struct PendingEntry {
+    doc: Map<String, Value>,
+    continuation: Vec<String>,
+}
+
+impl PendingEntry {
+    fn finish(mut self, continuation_field: &str) -> Value {
+        if !self.continuation.is_empty() {
+            self.doc.insert(continuation_field.to_string(), Value::String(self.continuation.join("\n")));
+        }
+        Value::Object(self.doc)
+    }
+}
+
+fn read_log_records<R: Read>(
+    source: R,
+    config: Option<&Value>,
+    emit: &mut dyn FnMut(Value) -> Result<(), IngestionError>,
+) -> Result<(), IngestionError> {
+    let options = LogOptions::from_config(config)?;
+    debug!("Parsing log lines with pattern '{}', on_unmatched={:?}", options.line_pattern, options.on_unmatched);
+
+    let reader = BufReader::new(decoding_reader(source, config)?);
+    let mut pending: Option<PendingEntry> = None;
+    let mut entry_count = 0;
+    let mut skipped_lines = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.map_err(|e| {
+            error!("Failed to read log line {}: {}", line_number, e);
+            IngestionError::Parse(format!("Log line {}: {}", line_number, e))
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(captures) = options.line_pattern.captures(&line) {
+            if let Some(entry) = pending.take() {
+                emit(entry.finish(&options.continuation_field))?;
+                entry_count += 1;
+            }
+            pending = Some(PendingEntry {
+                doc: entry_from_captures(&captures, &options, line_number)?,
+                continuation: Vec::new(),
+            });
+            continue;
+        }
+
+        let is_continuation = options.continuation_pattern.as_ref().is_some_and(|p| p.is_match(&line));
+        match (&mut pending, is_continuation, options.on_unmatched) {
+            (Some(entry), true, _) | (Some(entry), false, OnUnmatched::Attach) => entry.continuation.push(line),
+            (_, _, OnUnmatched::Fail) => {
+                error!("Log line {} does not match line_pattern", line_number);
+                return Err(IngestionError::Parse(format!("Log line {}: does not match line_pattern", line_number)));
+            },
+            _ => {
+                debug!("Skipping unmatched log line {}", line_number);
+                skipped_lines.push(line_number);
+            },
+        }
+    }
+
+    if let Some(entry) = pending.take() {
+        emit(entry.finish(&options.continuation_field))?;
+        entry_count += 1;
+    }
+
+    if !skipped_lines.is_empty() {
+        warn!("Skipped {} unmatched log lines: {:?}", skipped_lines.len(), skipped_lines);
+    }
+    info!("Parsed {} log entries", entry_count);
+    Ok(())
+}
+
+fn entry_from_captures(captures: &regex::Captures, options: &LogOptions, line_number: usize) -> Result<Map<String, Value>, IngestionError> {
+    let mut doc = Map::new();
+    doc.insert("line_number".to_string(), Value::from(line_number));
+    doc.insert(SOURCE_ROW_FIELD.to_string(), Value::from(line_number));
+
+    for name in options.line_pattern.capture_names().flatten() {
+        let value = match captures.name(name) {
+            Some(m) => options.schema.convert(name, m.as_str()).map_err(|e| {
+                error!("Log line {}, field '{}': {}", line_number, name, e);
+                IngestionError::Parse(format!("Log line {}, field '{}': {}", line_number, name, e))
+            })?,
+            None => Value::Null,
+        };
+        doc.insert(name.to_string(), value);
+    }
+
+    Ok(doc)
+}
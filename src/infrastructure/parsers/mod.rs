@@ -1,5 +1,16 @@
 pub mod csv_parser;
 pub mod json_parser;
+pub mod ndjson_parser;
 pub mod txt_parser;
+pub mod fixed_width_parser;
+pub mod log_parser;
 pub mod xml_parser;
-pub mod excel_parser;
\ No newline at end of file
+pub mod excel_parser;
+pub mod parquet_parser;
+pub mod avro_parser;
+pub mod pdf_parser;
+pub mod streaming;
+pub mod coercion;
+pub mod registry;
+pub mod builtin;
+pub mod encoding;
@@ -0,0 +1,89 @@
+use std::io::{BufRead, BufReader, Cursor, Read};
+use tracing::{debug, info, warn, error};
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream, SOURCE_ROW_FIELD}};
+use crate::infrastructure::parsers::{encoding::decoding_reader, streaming::spawn_batched};
+
+/// What to do with a line that is not valid JSON.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OnError {
+    Fail,
+    Skip,
+}
+
+fn on_error_from_config(config: Option<&serde_json::Value>) -> Result<OnError, IngestionError> {
+    match config.and_then(|c| c.get("on_error")).and_then(|v| v.as_str()) {
+        None | Some("fail") => Ok(OnError::Fail),
+        Some("skip") => Ok(OnError::Skip),
+        Some(other) => Err(IngestionError::Config(format!("Unknown NDJSON on_error mode '{}', expected fail or skip", other))),
+    }
+}
+
+pub fn parse_ndjson(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
+    parse_ndjson_with_config(bytes, None)
+}
+
+pub fn parse_ndjson_with_config(bytes: &[u8], config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
+    let mut documents = Vec::new();
+    read_ndjson_records(Cursor::new(bytes), config, &mut |doc| {
+        documents.push(doc);
+        Ok(())
+    })?;
+    Ok(documents)
+}
+
+/// Parses NDJSON incrementally from `stream`, yielding documents in batches of `batch_size`.
+pub fn stream_ndjson_with_config(stream: FileStream, config: Option<serde_json::Value>, batch_size: usize) -> DocumentBatchStream {
+    spawn_batched(stream, batch_size, move |reader, emit| {
+        read_ndjson_records(reader, config.as_ref(), emit)
+    })
+}
+
+fn read_ndjson_records<R: Read>(
+    source: R,
+    config: Option<&serde_json::Value>,
+    emit: &mut dyn FnMut(serde_json::Value) -> Result<(), IngestionError>,
+) -> Result<(), IngestionError> {
+    let on_error = on_error_from_config(config)?;
+    debug!("Parsing NDJSON content with on_error={:?}", on_error);
+
+    let reader = BufReader::new(decoding_reader(source, config)?);
+    let mut parsed = 0;
+    let mut skipped_lines = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.map_err(|e| {
+            error!("Failed to read NDJSON line {}: {}", line_number, e);
+            IngestionError::Parse(format!("NDJSON line {}: {}", line_number, e))
+        })?;
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<serde_json::Value>(trimmed) {
+            Ok(mut doc) => {
+                if let Some(fields) = doc.as_object_mut() {
+                    fields.insert(SOURCE_ROW_FIELD.to_string(), serde_json::Value::from(line_number));
+                }
+                emit(doc)?;
+                parsed += 1;
+            },
+            Err(e) if on_error == OnError::Skip => {
+                warn!("Skipping malformed NDJSON line {}: {}", line_number, e);
+                skipped_lines.push(line_number);
+            },
+            Err(e) => {
+                error!("Malformed NDJSON line {}: {}", line_number, e);
+                return Err(IngestionError::Parse(format!("NDJSON line {}: {}", line_number, e)));
+            }
+        }
+    }
+
+    if !skipped_lines.is_empty() {
+        warn!("Skipped {} malformed NDJSON lines: {:?}", skipped_lines.len(), skipped_lines);
+    }
+    info!("Parsed {} documents from NDJSON", parsed);
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::fs::{File, OpenOptions};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use parquet::record::Field;
+use tracing::{debug, info, error};
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream}};
+use crate::infrastructure::{parsers::{coercion::{decimal_bytes_to_json, decimals_as_string}, streaming::spawn_batched}, spool::SpoolFile};
+
+pub fn parse_parquet(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
+    parse_parquet_with_config(bytes, None)
+}
+
+pub fn parse_parquet_with_config(bytes: &[u8], config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
+    let as_string = decimals_as_string(config);
+    let mut documents = Vec::new();
+    read_parquet_records(Bytes::copy_from_slice(bytes), as_string, &mut |doc| {
+        documents.push(doc);
+        Ok(())
+    })?;
+    Ok(documents)
+}
+
+/// Converts a Parquet file row group by row group, yielding documents in batches of `batch_size`.
+///
+/// Parquet keeps its metadata in the footer, so the file is spooled to a temporary file
+/// and read back by column chunk; only one row group and one batch of JSON documents are
+/// held in memory at a time.
+pub fn stream_parquet_with_config(stream: FileStream, config: Option<serde_json::Value>, batch_size: usize) -> DocumentBatchStream {
+    let as_string = decimals_as_string(config.as_ref());
+    spawn_batched(stream, batch_size, move |mut reader, emit| {
+        let spool = SpoolFile::new("parquet");
+        let spool_error = |e: std::io::Error| {
+            error!("Failed to spool Parquet file to {}: {}", spool.path().display(), e);
+            IngestionError::Parse(e.to_string())
+        };
+
+        let mut file = OpenOptions::new().write(true).create_new(true).open(spool.path()).map_err(spool_error)?;
+        let spooled = std::io::copy(&mut reader, &mut file).map_err(spool_error)?;
+        drop(file);
+        debug!("Spooled {} bytes of Parquet data to {}", spooled, spool.path().display());
+
+        read_parquet_records(File::open(spool.path()).map_err(spool_error)?, as_string, emit)
+    })
+}
+
+fn read_parquet_records<R: ChunkReader + 'static>(
+    source: R,
+    decimals_as_string: bool,
+    emit: &mut dyn FnMut(serde_json::Value) -> Result<(), IngestionError>,
+) -> Result<(), IngestionError> {
+    let reader = SerializedFileReader::new(source).map_err(|e| {
+        error!("Failed to open Parquet file: {}", e);
+        IngestionError::Parse(e.to_string())
+    })?;
+
+    let metadata = reader.metadata();
+    let row_groups = metadata.num_row_groups();
+    info!("Parquet file has {} row groups and {} rows", row_groups, metadata.file_metadata().num_rows());
+
+    let mut row_count = 0;
+    for i in 0..row_groups {
+        let row_group = reader.get_row_group(i).map_err(|e| {
+            error!("Failed to read Parquet row group {}: {}", i, e);
+            IngestionError::Parse(e.to_string())
+        })?;
+        let rows = row_group.get_row_iter(None).map_err(|e| {
+            error!("Failed to iterate Parquet row group {}: {}", i, e);
+            IngestionError::Parse(e.to_string())
+        })?;
+
+        for row in rows {
+            let row = row.map_err(|e| {
+                error!("Failed to decode Parquet row {} in row group {}: {}", row_count + 1, i, e);
+                IngestionError::Parse(e.to_string())
+            })?;
+            let doc = row.get_column_iter()
+                .map(|(name, field)| (name.clone(), field_to_json(field, decimals_as_string)))
+                .collect();
+            emit(serde_json::Value::Object(doc))?;
+            row_count += 1;
+        }
+        debug!("Converted Parquet row group {} ({} rows so far)", i, row_count);
+    }
+
+    info!("Parsed {} rows from Parquet", row_count);
+    Ok(())
+}
+
+/// Maps a Parquet field to JSON, keeping decimals of any width and timestamps at full precision.
+fn field_to_json(field: &Field, decimals_as_string: bool) -> serde_json::Value {
+    match field {
+        Field::Decimal(decimal) => decimal_bytes_to_json(decimal.data(), decimal.scale() as u32, decimals_as_string),
+        Field::TimestampMillis(ts) => timestamp_to_json(DateTime::<Utc>::from_timestamp_millis(*ts)),
+        Field::TimestampMicros(ts) => timestamp_to_json(DateTime::<Utc>::from_timestamp_micros(*ts)),
+        Field::Group(row) => serde_json::Value::Object(
+            row.get_column_iter()
+                .map(|(name, field)| (name.clone(), field_to_json(field, decimals_as_string)))
+                .collect(),
+        ),
+        Field::ListInternal(list) => serde_json::Value::Array(
+            list.elements().iter().map(|element| field_to_json(element, decimals_as_string)).collect(),
+        ),
+        Field::MapInternal(map) => serde_json::Value::Object(
+            map.entries()
+                .iter()
+                .map(|(key, value)| {
+                    let key = match field_to_json(key, decimals_as_string) {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (key, field_to_json(value, decimals_as_string))
+                })
+                .collect(),
+        ),
+        other => other.to_json_value(),
+    }
+}
+
+fn timestamp_to_json(ts: Option<DateTime<Utc>>) -> serde_json::Value {
+    ts.map(|dt| serde_json::Value::String(dt.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)))
+        .unwrap_or(serde_json::Value::Null)
+}
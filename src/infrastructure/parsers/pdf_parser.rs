@@ -0,0 +1,249 @@
+use std::panic::{self, AssertUnwindSafe};
+use pdf_extract::{output_doc, Document, MediaBox, OutputDev, OutputError, Transform};
+use serde_json::{Map, Value};
+use tracing::{debug, info, error};
+use crate::domain::error::IngestionError;
+
+/// What to emit for each page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PdfMode {
+    /// One document per page with its text.
+    Pages,
+    /// One document per row of each detected table.
+    Tables,
+}
+
+struct PdfOptions {
+    mode: PdfMode,
+    min_columns: usize,
+    /// Horizontal gap, in font sizes, that separates two table cells.
+    column_gap: f64,
+}
+
+impl PdfOptions {
+    fn from_config(config: Option<&Value>) -> Result<Self, IngestionError> {
+        let mode = match config.and_then(|c| c.get("mode")).and_then(|v| v.as_str()) {
+            None | Some("pages") => PdfMode::Pages,
+            Some("tables") => PdfMode::Tables,
+            Some(other) => return Err(IngestionError::Config(format!("Unknown PDF mode '{}', expected pages or tables", other))),
+        };
+        let min_columns = config.and_then(|c| c.get("min_columns")).and_then(|v| v.as_u64()).unwrap_or(2).max(2) as usize;
+        let column_gap = config.and_then(|c| c.get("column_gap")).and_then(|v| v.as_f64()).unwrap_or(1.0);
+        Ok(Self { mode, min_columns, column_gap })
+    }
+}
+
+pub fn parse_pdf(bytes: &[u8]) -> Result<Vec<Value>, IngestionError> {
+    parse_pdf_with_config(bytes, None)
+}
+
+pub fn parse_pdf_with_config(bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+    let options = PdfOptions::from_config(config)?;
+    debug!("Parsing PDF with mode={:?}", options.mode);
+
+    let documents = match options.mode {
+        PdfMode::Pages => extract_pages(bytes)?.into_iter()
+            .enumerate()
+            .map(|(i, text)| serde_json::json!({
+                "page_number": i + 1,
+                "text": text.trim(),
+            }))
+            .collect::<Vec<_>>(),
+        PdfMode::Tables => {
+            let mut documents = Vec::new();
+            for (i, lines) in extract_layout(bytes)?.iter().enumerate() {
+                let tables = detect_tables(lines, &options);
+                debug!("Found {} tables on PDF page {}", tables.len(), i + 1);
+                for (table_index, table) in tables.iter().enumerate() {
+                    documents.extend(table_rows(table, i + 1, table_index + 1));
+                }
+            }
+            info!("Extracted {} table rows from PDF", documents.len());
+            documents
+        },
+    };
+
+    Ok(documents)
+}
+
+fn extract_pages(bytes: &[u8]) -> Result<Vec<String>, IngestionError> {
+    // pdf-extract panics on some malformed documents instead of returning an error
+    let result = panic::catch_unwind(AssertUnwindSafe(|| pdf_extract::extract_text_from_mem_by_pages(bytes)));
+    match result {
+        Ok(Ok(pages)) => {
+            info!("Extracted text from {} PDF pages", pages.len());
+            Ok(pages)
+        },
+        Ok(Err(e)) => {
+            error!("Failed to extract PDF text: {}", e);
+            Err(IngestionError::Parse(e.to_string()))
+        },
+        Err(_) => {
+            error!("PDF text extraction aborted on malformed document");
+            Err(IngestionError::Parse("Malformed PDF document".to_string()))
+        },
+    }
+}
+
+/// A run of glyphs on one baseline.
+struct TextLine {
+    y: f64,
+    glyphs: Vec<Glyph>,
+}
+
+struct Glyph {
+    x: f64,
+    end_x: f64,
+    size: f64,
+    text: String,
+}
+
+/// Collects positioned glyphs per page so that column gaps survive extraction.
+#[derive(Default)]
+struct LayoutCollector {
+    pages: Vec<Vec<TextLine>>,
+}
+
+impl OutputDev for LayoutCollector {
+    fn begin_page(&mut self, _page_num: u32, _media_box: &MediaBox, _art_box: Option<(f64, f64, f64, f64)>) -> Result<(), OutputError> {
+        self.pages.push(Vec::new());
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        if let Some(lines) = self.pages.last_mut() {
+            // PDF y coordinates grow upwards
+            lines.sort_by(|a, b| b.y.total_cmp(&a.y));
+            for line in lines.iter_mut() {
+                line.glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+            }
+        }
+        Ok(())
+    }
+
+    fn output_character(&mut self, trm: &Transform, width: f64, _spacing: f64, font_size: f64, char: &str) -> Result<(), OutputError> {
+        let size = ((font_size * (trm.m11 + trm.m21)) * (font_size * (trm.m12 + trm.m22))).abs().sqrt();
+        let glyph = Glyph {
+            x: trm.m31,
+            end_x: trm.m31 + width * size,
+            size,
+            text: char.to_string(),
+        };
+        let y = trm.m32;
+
+        let Some(lines) = self.pages.last_mut() else {
+            return Ok(());
+        };
+        match lines.iter_mut().find(|line| (line.y - y).abs() < size * 0.5) {
+            Some(line) => line.glyphs.push(glyph),
+            None => lines.push(TextLine { y, glyphs: vec![glyph] }),
+        }
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+fn extract_layout(bytes: &[u8]) -> Result<Vec<Vec<TextLine>>, IngestionError> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let doc = Document::load_mem(bytes).map_err(|e| e.to_string())?;
+        let mut collector = LayoutCollector::default();
+        output_doc(&doc, &mut collector).map_err(|e| e.to_string())?;
+        Ok::<_, String>(collector.pages)
+    }));
+    match result {
+        Ok(Ok(pages)) => Ok(pages),
+        Ok(Err(e)) => {
+            error!("Failed to extract PDF layout: {}", e);
+            Err(IngestionError::Parse(e))
+        },
+        Err(_) => {
+            error!("PDF layout extraction aborted on malformed document");
+            Err(IngestionError::Parse("Malformed PDF document".to_string()))
+        },
+    }
+}
+
+/// Splits a line into cells wherever the horizontal gap exceeds `column_gap` font sizes.
+fn split_cells(line: &TextLine, column_gap: f64) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut last_end: Option<f64> = None;
+
+    for glyph in &line.glyphs {
+        if let Some(end) = last_end {
+            let gap = glyph.x - end;
+            if gap > glyph.size * column_gap {
+                cells.push(std::mem::take(&mut current));
+            } else if gap > glyph.size * 0.1 && !current.ends_with(' ') {
+                current.push(' ');
+            }
+        }
+        current.push_str(&glyph.text);
+        last_end = Some(glyph.end_x);
+    }
+    cells.push(current);
+
+    cells.into_iter()
+        .map(|cell| cell.trim().to_string())
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+/// Tables are runs of consecutive lines with the same number of cells; the first line is the header.
+fn detect_tables(lines: &[TextLine], options: &PdfOptions) -> Vec<Vec<Vec<String>>> {
+    let mut tables = Vec::new();
+    let mut current: Vec<Vec<String>> = Vec::new();
+
+    for line in lines {
+        let cells = split_cells(line, options.column_gap);
+        let continues = current.first().map(|header| header.len() == cells.len()).unwrap_or(true);
+
+        if cells.len() >= options.min_columns && continues {
+            current.push(cells);
+            continue;
+        }
+        if current.len() > 1 {
+            tables.push(std::mem::take(&mut current));
+        }
+        current.clear();
+        if cells.len() >= options.min_columns {
+            current.push(cells);
+        }
+    }
+    if current.len() > 1 {
+        tables.push(current);
+    }
+    tables
+}
+
+/// Rows are keyed by the header cells; where they came from is kept under `_pdf` so a
+/// column named like `page` or `row_number` cannot overwrite it.
+fn table_rows(table: &[Vec<String>], page_number: usize, table_number: usize) -> Vec<Value> {
+    let (header, rows) = table.split_first().expect("tables have a header row");
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut doc = Map::new();
+            for (name, cell) in header.iter().zip(row) {
+                doc.insert(name.clone(), Value::String(cell.clone()));
+            }
+            doc.insert("_pdf".to_string(), serde_json::json!({
+                "page": page_number,
+                "table": table_number,
+                "row": i + 1,
+            }));
+            Value::Object(doc)
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use serde_json::Value;
+use tracing::debug;
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream}};
+use crate::infrastructure::parsers::builtin;
+
+/// JSON type accepted by a `parser_config` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array,
+    Object,
+    /// A string holding a regular expression.
+    Regex,
+}
+
+impl OptionType {
+    pub fn matches(self, value: &Value) -> bool {
+        match self {
+            OptionType::String | OptionType::Regex => value.is_string(),
+            OptionType::Integer => value.is_u64() || value.is_i64(),
+            OptionType::Number => value.is_number(),
+            OptionType::Boolean => value.is_boolean(),
+            OptionType::Array => value.is_array(),
+            OptionType::Object => value.is_object(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OptionType::String => "string",
+            OptionType::Integer => "integer",
+            OptionType::Number => "number",
+            OptionType::Boolean => "boolean",
+            OptionType::Array => "array",
+            OptionType::Object => "object",
+            OptionType::Regex => "regex",
+        }
+    }
+}
+
+/// A `parser_config` key understood by a parser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigOption {
+    pub name: &'static str,
+    /// Accepted JSON types; `null` is always accepted.
+    pub types: &'static [OptionType],
+    pub description: &'static str,
+}
+
+impl ConfigOption {
+    pub const fn new(name: &'static str, types: &'static [OptionType], description: &'static str) -> Self {
+        Self { name, types, description }
+    }
+}
+
+/// A parser that can be registered with a [`ParserRegistry`].
+///
+/// Downstream crates implement this trait and register their parser with
+/// `ParserRegistry::register` to support formats the built-in parsers do not.
+pub trait FileParser: Send + Sync {
+    /// Short name shown in logs and error messages.
+    fn name(&self) -> &str;
+
+    /// File types (usually extensions) this parser handles, most specific first.
+    fn file_types(&self) -> &[&str];
+
+    /// MIME types that map to this parser's first file type.
+    fn mime_types(&self) -> &[&str] {
+        &[]
+    }
+
+    /// The `parser_config` options this parser understands.
+    fn config_schema(&self) -> Vec<ConfigOption> {
+        Vec::new()
+    }
+
+    /// Whether this parser handles `file_type` with the given config. Lets several
+    /// parsers share a file type, e.g. `txt` with `fields` is fixed-width.
+    fn accepts(&self, _file_type: &str, _config: Option<&Value>) -> bool {
+        true
+    }
+
+    fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError>;
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn parse_stream(&self, _stream: FileStream, _config: Option<Value>, _batch_size: usize) -> Result<DocumentBatchStream, IngestionError> {
+        Err(IngestionError::Parse(format!("Streaming is not supported by the {} parser", self.name())))
+    }
+}
+
+/// Parsers by file type and MIME type; later registrations take precedence.
+#[derive(Clone, Default)]
+pub struct ParserRegistry {
+    parsers: Vec<Arc<dyn FileParser>>,
+}
+
+impl ParserRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with every parser shipped in this crate.
+    pub fn with_builtin_parsers() -> Self {
+        let mut registry = Self::new();
+        builtin::register_all(&mut registry);
+        registry
+    }
+
+    pub fn register(&mut self, parser: impl FileParser + 'static) -> &mut Self {
+        debug!("Registering parser '{}' for {:?}", parser.name(), parser.file_types());
+        self.parsers.push(Arc::new(parser));
+        self
+    }
+
+    pub fn parsers(&self) -> &[Arc<dyn FileParser>] {
+        &self.parsers
+    }
+
+    /// The parser for `file_type`, preferring the most recently registered one that accepts `config`.
+    pub fn resolve(&self, file_type: &str, config: Option<&Value>) -> Option<&dyn FileParser> {
+        self.parsers.iter()
+            .rev()
+            .find(|parser| parser.file_types().contains(&file_type) && parser.accepts(file_type, config))
+            .map(|parser| parser.as_ref())
+    }
+
+    pub fn file_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self.parsers.iter()
+            .flat_map(|parser| parser.file_types().iter().map(|t| t.to_string()))
+            .collect();
+        types.sort();
+        types.dedup();
+        types
+    }
+
+    /// Maps a `Content-Type` header (parameters ignored) to a file type.
+    pub fn file_type_for_mime(&self, content_type: &str) -> Option<String> {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_lowercase();
+        self.parsers.iter()
+            .rev()
+            .find(|parser| parser.mime_types().contains(&mime.as_str()))
+            .and_then(|parser| parser.file_types().first())
+            .map(|file_type| file_type.to_string())
+    }
+}
@@ -0,0 +1,64 @@
+use tokio::sync::mpsc;
+use tokio_util::io::SyncIoBridge;
+use tracing::{debug, error};
+use crate::domain::{error::IngestionError, ports::{DocumentBatchStream, FileStream}};
+
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Reads `batch_size` from a parser config, falling back to the default.
+pub fn batch_size_from_config(config: Option<&serde_json::Value>) -> usize {
+    config
+        .and_then(|c| c.get("batch_size"))
+        .and_then(|v| v.as_u64())
+        .filter(|&n| n > 0)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_BATCH_SIZE)
+}
+
+/// Runs a blocking record parser over `stream` on the blocking thread pool and
+/// yields its documents in batches of at most `batch_size`.
+///
+/// The channel holds a single batch, so the parser is back-pressured by the
+/// consumer and memory stays bounded regardless of the file size.
+pub fn spawn_batched<F>(stream: FileStream, batch_size: usize, parse: F) -> DocumentBatchStream
+where
+    F: FnOnce(SyncIoBridge<FileStream>, &mut dyn FnMut(serde_json::Value) -> Result<(), IngestionError>) -> Result<(), IngestionError>
+        + Send
+        + 'static,
+{
+    let (tx, rx) = mpsc::channel::<Result<Vec<serde_json::Value>, IngestionError>>(1);
+
+    tokio::task::spawn_blocking(move || {
+        let reader = SyncIoBridge::new(stream);
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut batches_sent = 0;
+
+        let result = parse(reader, &mut |doc| {
+            batch.push(doc);
+            if batch.len() >= batch_size {
+                let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+                tx.blocking_send(Ok(full))
+                    .map_err(|_| IngestionError::Parse("Document stream consumer was dropped".to_string()))?;
+                batches_sent += 1;
+                debug!("Emitted batch {} of up to {} documents", batches_sent, batch_size);
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {
+                if !batch.is_empty() {
+                    let _ = tx.blocking_send(Ok(batch));
+                }
+            },
+            Err(e) => {
+                error!("Streaming parse failed after {} batches: {}", batches_sent, e);
+                let _ = tx.blocking_send(Err(e));
+            }
+        }
+    });
+
+    Box::pin(futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    }))
+}
@@ -1,13 +1,14 @@
-use tracing::{debug, info, error};
+use tracing::{debug, info};
 use crate::domain::error::IngestionError;
+use crate::infrastructure::parsers::encoding::decode_bytes;
 
 pub fn parse_txt(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
-    debug!("Converting bytes to UTF-8 string");
-    let content = String::from_utf8(bytes.to_vec())
-        .map_err(|e| {
-            error!("Failed to convert text file to UTF-8: {}", e);
-            IngestionError::Parse(e.to_string())
-        })?;
+    parse_txt_with_config(bytes, None)
+}
+
+pub fn parse_txt_with_config(bytes: &[u8], config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
+    debug!("Decoding text file to UTF-8");
+    let content = decode_bytes(bytes, config)?;
     
     let line_count = content.lines().count();
     debug!("Text file contains {} lines", line_count);
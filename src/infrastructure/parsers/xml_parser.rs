@@ -1,58 +1,133 @@
 use tracing::{debug, error};
 use crate::domain::error::IngestionError;
+use crate::infrastructure::parsers::encoding::strip_bom;
 use quick_xml::Reader;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::QName;
 use serde_json::{Value, Map};
 
+/// How record elements are located in the document.
+#[derive(Debug, Clone)]
+enum RecordPath {
+    /// `/Orders/Order`: the full element path from the root.
+    Absolute(Vec<String>),
+    /// `Order` or `//Batch/Order`: the trailing segments of the element path.
+    Suffix(Vec<String>),
+}
+
+impl RecordPath {
+    fn parse(path: &str) -> Result<Self, IngestionError> {
+        let (rest, absolute) = if let Some(rest) = path.strip_prefix("//") {
+            (rest, false)
+        } else if let Some(rest) = path.strip_prefix('/') {
+            (rest, true)
+        } else {
+            (path, false)
+        };
+
+        let parts: Vec<String> = rest.split('/').map(|s| s.to_string()).collect();
+        if parts.iter().any(|s| s.is_empty()) {
+            return Err(IngestionError::Config(format!("Invalid XML record_path '{}'", path)));
+        }
+        Ok(if absolute { Self::Absolute(parts) } else { Self::Suffix(parts) })
+    }
+
+    fn matches(&self, stack: &[String]) -> bool {
+        match self {
+            Self::Absolute(parts) => stack == parts.as_slice(),
+            Self::Suffix(parts) => stack.ends_with(parts),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct XmlOptions {
+    record_element: String,
+    record_path: RecordPath,
+    include_attributes: bool,
+    attribute_prefix: String,
+    text_key: String,
+    strip_namespaces: bool,
+}
+
+impl XmlOptions {
+    fn from_config(config: Option<&Value>) -> Result<Self, IngestionError> {
+        let get_str = |key: &str| config.and_then(|c| c.get(key)).and_then(|v| v.as_str());
+
+        let strip_namespaces = match get_str("namespaces").unwrap_or("strip") {
+            "strip" => true,
+            "keep" => false,
+            other => return Err(IngestionError::Config(format!("Unknown XML namespaces mode '{}', expected strip or keep", other))),
+        };
+
+        let record_element = get_str("record_path").unwrap_or("record").to_string();
+
+        Ok(Self {
+            record_path: RecordPath::parse(&record_element)?,
+            record_element,
+            include_attributes: config.and_then(|c| c.get("include_attributes")).and_then(|v| v.as_bool()).unwrap_or(true),
+            attribute_prefix: get_str("attribute_prefix").unwrap_or("@").to_string(),
+            text_key: get_str("text_key").unwrap_or("#text").to_string(),
+            strip_namespaces,
+        })
+    }
+
+    fn name(&self, name: QName) -> String {
+        let bytes = if self.strip_namespaces { name.local_name().into_inner() } else { name.into_inner() };
+        String::from_utf8_lossy(bytes).to_string()
+    }
+}
+
+/// An element of the current record that is still open.
+struct Frame {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
 pub fn parse_xml(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError> {
-    debug!("Parsing XML content");
-    
-    let mut reader = Reader::from_reader(bytes);
+    parse_xml_with_config(bytes, None)
+}
+
+pub fn parse_xml_with_config(bytes: &[u8], config: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>, IngestionError> {
+    let options = XmlOptions::from_config(config)?;
+    debug!("Parsing XML content with options: {:?}", options);
+
+    let mut reader = Reader::from_reader(strip_bom(bytes));
     reader.trim_text(true);
-    
+
     let mut buf = Vec::new();
     let mut records = Vec::new();
-    let mut current_record: Option<Map<String, Value>> = None;
-    let mut current_field = String::new();
-    
+    let mut path: Vec<String> = Vec::new();
+    // Open elements of the record being built; empty when outside a record
+    let mut frames: Vec<Frame> = Vec::new();
+
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(ref e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                if name == "record" {
-                    current_record = Some(Map::new());
-                    // Extract attributes
-                    for attr in e.attributes() {
-                        if let Ok(attr) = attr {
-                            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
-                            let value = String::from_utf8_lossy(&attr.value).to_string();
-                            if let Some(ref mut record) = current_record {
-                                record.insert(key, Value::String(value));
-                            }
-                        }
-                    }
-                } else if current_record.is_some() {
-                    current_field = name;
-                }
+                open_element(e, &options, &mut path, &mut frames);
+            },
+            Ok(Event::Empty(ref e)) => {
+                open_element(e, &options, &mut path, &mut frames);
+                close_element(&options, &mut path, &mut frames, &mut records);
             },
             Ok(Event::Text(e)) => {
-                if let Some(ref mut record) = current_record {
-                    if !current_field.is_empty() {
-                        let text = e.unescape().unwrap_or_default().to_string();
-                        record.insert(current_field.clone(), Value::String(text));
-                    }
+                if let Some(frame) = frames.last_mut() {
+                    let text = e.unescape().map_err(|e| {
+                        error!("Error unescaping XML text: {}", e);
+                        IngestionError::Parse(e.to_string())
+                    })?;
+                    frame.text.push_str(&text);
                 }
             },
-            Ok(Event::End(ref e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                if name == "record" {
-                    if let Some(record) = current_record.take() {
-                        records.push(Value::Object(record));
-                    }
-                } else if current_record.is_some() {
-                    current_field.clear();
+            Ok(Event::CData(e)) => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.text.push_str(&String::from_utf8_lossy(&e));
                 }
             },
+            Ok(Event::End(_)) => {
+                close_element(&options, &mut path, &mut frames, &mut records);
+            },
             Ok(Event::Eof) => break,
             Err(e) => {
                 error!("Error parsing XML: {}", e);
@@ -62,12 +137,83 @@ pub fn parse_xml(bytes: &[u8]) -> Result<Vec<serde_json::Value>, IngestionError>
         }
         buf.clear();
     }
-    
+
     if records.is_empty() {
         error!("No records found in XML");
-        return Err(IngestionError::Parse("No records found in XML".to_string()));
+        return Err(IngestionError::Parse(format!("No records found in XML matching record_path '{}'", options.record_element)));
     }
-    
+
     debug!("Parsed {} XML records", records.len());
     Ok(records)
-}
\ No newline at end of file
+}
+
+fn open_element(e: &BytesStart, options: &XmlOptions, path: &mut Vec<String>, frames: &mut Vec<Frame>) {
+    let name = options.name(e.name());
+    path.push(name.clone());
+
+    if frames.is_empty() && !options.record_path.matches(path) {
+        return;
+    }
+
+    let mut fields = Map::new();
+    if options.include_attributes {
+        for attr in e.attributes().flatten() {
+            if options.strip_namespaces && attr.key.as_namespace_binding().is_some() {
+                continue;
+            }
+            let key = format!("{}{}", options.attribute_prefix, options.name(attr.key));
+            let value = attr.unescape_value()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|_| String::from_utf8_lossy(&attr.value).to_string());
+            fields.insert(key, Value::String(value));
+        }
+    }
+    frames.push(Frame { name, fields, text: String::new() });
+}
+
+fn close_element(options: &XmlOptions, path: &mut Vec<String>, frames: &mut Vec<Frame>, records: &mut Vec<Value>) {
+    path.pop();
+
+    // Frames are only opened inside records, so an element outside one has no frame
+    let Some(frame) = frames.pop() else {
+        return;
+    };
+    match frames.last_mut() {
+        Some(parent) => {
+            let value = frame_value(frame.fields, frame.text, options);
+            insert_child(&mut parent.fields, frame.name, value);
+        },
+        None => {
+            let mut fields = frame.fields;
+            if !frame.text.is_empty() {
+                fields.insert(options.text_key.clone(), Value::String(frame.text));
+            }
+            records.push(Value::Object(fields));
+        },
+    }
+}
+
+/// Leaf elements become their text, anything with attributes or children becomes an object.
+fn frame_value(mut fields: Map<String, Value>, text: String, options: &XmlOptions) -> Value {
+    if fields.is_empty() {
+        return Value::String(text);
+    }
+    if !text.is_empty() {
+        fields.insert(options.text_key.clone(), Value::String(text));
+    }
+    Value::Object(fields)
+}
+
+/// Repeated sibling elements are collected into an array.
+fn insert_child(fields: &mut Map<String, Value>, name: String, value: Value) {
+    match fields.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        },
+        None => {
+            fields.insert(name, value);
+        },
+    }
+}
@@ -0,0 +1,59 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+use crate::domain::{error::IngestionError, models::IngestionConfigRule, rule_matching::RuleMatcher};
+
+/// How long cached rules are used before they are reloaded, unless invalidated earlier.
+pub const DEFAULT_RULE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+struct CachedRules {
+    loaded_at: Instant,
+    rules: Arc<RuleMatcher>,
+}
+
+/// Holds the compiled rules between lookups so the config store is queried once per TTL
+/// (or change notification) instead of once per file.
+pub struct RuleCache {
+    ttl: Duration,
+    cached: Mutex<Option<CachedRules>>,
+}
+
+impl RuleCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, cached: Mutex::new(None) }
+    }
+
+    /// The cached rules, reloaded with `load` when missing or older than the TTL.
+    ///
+    /// Concurrent callers wait for a single reload rather than each querying the store.
+    pub async fn get_or_load<F, Fut>(&self, load: F) -> Result<Arc<RuleMatcher>, IngestionError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<IngestionConfigRule>, IngestionError>>,
+    {
+        let mut cached = self.cached.lock().await;
+        if let Some(entry) = cached.as_ref().filter(|entry| entry.loaded_at.elapsed() < self.ttl) {
+            debug!("Using {} cached config rules", entry.rules.rules().len());
+            return Ok(entry.rules.clone());
+        }
+
+        let rules = Arc::new(RuleMatcher::new(load().await?)?);
+        info!("✅ Loaded {} config rules into cache", rules.rules().len());
+        *cached = Some(CachedRules { loaded_at: Instant::now(), rules: rules.clone() });
+        Ok(rules)
+    }
+
+    /// Drops the cached rules so the next lookup reloads them.
+    pub async fn invalidate(&self) {
+        debug!("Invalidating config rule cache");
+        *self.cached.lock().await = None;
+    }
+}
+
+impl Default for RuleCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RULE_CACHE_TTL)
+    }
+}
@@ -0,0 +1,182 @@
+use regex::Regex;
+use serde_json::Value;
+use tracing::{debug, error};
+use crate::domain::{error::IngestionError, document_schema::DocumentSchema, models::IngestionConfigRule, transform::Transform};
+use crate::infrastructure::parsers::registry::{ConfigOption, OptionType, ParserRegistry};
+
+/// Checks configuration rules against the options published by the registered parsers.
+///
+/// Config repositories run every rule through this when rules are loaded, so a typo in
+/// `parser_config` fails loudly instead of silently changing how files are parsed.
+#[derive(Clone)]
+pub struct RuleValidator {
+    registry: ParserRegistry,
+}
+
+impl RuleValidator {
+    pub fn new(registry: ParserRegistry) -> Self {
+        Self { registry }
+    }
+
+    pub fn validate(&self, rule: &IngestionConfigRule) -> Result<(), IngestionError> {
+        self.check(rule).map_err(|message| {
+            error!("Invalid rule '{}' for table '{}': {}", rule.pattern, rule.target_table, message);
+            IngestionError::Config(format!("Rule '{}' (target '{}'): {}", rule.pattern, rule.target_table, message))
+        })
+    }
+
+    fn check(&self, rule: &IngestionConfigRule) -> Result<(), String> {
+        Regex::new(&rule.pattern).map_err(|e| format!("invalid regex in pattern: {}", e))?;
+        for (field, pattern) in [("bucket", &rule.matchers.bucket), ("content_type", &rule.matchers.content_type)] {
+            if let Some(pattern) = pattern {
+                Regex::new(pattern).map_err(|e| format!("invalid regex in matchers.{}: {}", field, e))?;
+            }
+        }
+        if rule.version == 0 {
+            return Err("version must be at least 1".to_string());
+        }
+        if let (Some(from), Some(until)) = (rule.valid_from, rule.valid_until) {
+            if from >= until {
+                return Err(format!("valid_from {} is not before valid_until {}", from, until));
+            }
+        }
+        if let (Some(min), Some(max)) = (rule.matchers.min_size, rule.matchers.max_size) {
+            if min > max {
+                return Err(format!("matchers.min_size {} is greater than matchers.max_size {}", min, max));
+            }
+        }
+        if let Some(ratio) = rule.max_reject_ratio.filter(|ratio| !(0.0..=1.0).contains(ratio)) {
+            return Err(format!("max_reject_ratio must be between 0 and 1, got {}", ratio));
+        }
+        let config_message = |e: IngestionError| match e {
+            IngestionError::Config(message) => message,
+            other => other.to_string(),
+        };
+        Transform::from_config(rule.transform.as_ref()).map_err(config_message)?;
+        DocumentSchema::from_config(rule.schema.as_ref()).map_err(config_message)?;
+
+        let config = match &rule.parser_config {
+            None | Some(Value::Null) => None,
+            Some(Value::Object(config)) => Some(config),
+            Some(other) => return Err(format!("parser_config must be an object, got {}", json_type(other))),
+        };
+
+        // With a forced file type only that parser's options apply; otherwise those of the
+        // parsers for the extension the pattern ends in, or of any parser when it names none
+        let schema: Vec<ConfigOption> = match rule.file_type.as_deref().map(str::to_lowercase) {
+            Some(file_type) => self.registry.resolve(&file_type, rule.parser_config.as_ref())
+                .ok_or_else(|| format!("no parser is registered for file_type '{}'", file_type))?
+                .config_schema(),
+            None => {
+                let file_types = pattern_file_types(&rule.pattern);
+                let candidates: Vec<_> = self.registry.parsers().iter()
+                    .filter(|parser| file_types.iter().any(|t| parser.file_types().contains(&t.as_str())))
+                    .collect();
+                if candidates.is_empty() {
+                    self.registry.parsers().iter().flat_map(|parser| parser.config_schema()).collect()
+                } else {
+                    debug!("Checking parser_config of rule '{}' against the parsers for {:?}", rule.pattern, file_types);
+                    candidates.into_iter().flat_map(|parser| parser.config_schema()).collect()
+                }
+            },
+        };
+
+        for (key, value) in config.into_iter().flatten() {
+            let options: Vec<&ConfigOption> = schema.iter().filter(|option| option.name == key).collect();
+            if options.is_empty() {
+                return Err(match closest_option(key, &schema) {
+                    Some(suggestion) => format!("unknown parser_config key '{}', did you mean '{}'?", key, suggestion),
+                    None => format!("unknown parser_config key '{}'", key),
+                });
+            }
+            check_value(key, value, &options)?;
+        }
+
+        debug!("✅ Rule '{}' for table '{}' is valid", rule.pattern, rule.target_table);
+        Ok(())
+    }
+}
+
+impl Default for RuleValidator {
+    fn default() -> Self {
+        Self::new(ParserRegistry::with_builtin_parsers())
+    }
+}
+
+/// File types named by a literal extension at the end of `pattern`, e.g. `csv` and `tsv`
+/// for `.*\.(csv|tsv)$` or `csv` for `.*\.csv\.gz$`; empty when it ends in none.
+fn pattern_file_types(pattern: &str) -> Vec<String> {
+    let mut rest = pattern.strip_suffix('$').unwrap_or(pattern);
+    loop {
+        let Some((head, extension)) = rest.rsplit_once("\\.") else {
+            return Vec::new();
+        };
+        let alternatives = extension.strip_prefix("(?:").or_else(|| extension.strip_prefix('('))
+            .and_then(|group| group.strip_suffix(')'))
+            .unwrap_or(extension);
+        let types: Vec<String> = alternatives.split('|').map(str::to_lowercase).collect();
+        if types.iter().any(|t| t.is_empty() || !t.chars().all(|c| c.is_ascii_alphanumeric())) {
+            return Vec::new();
+        }
+        // Compressed objects are parsed by the extension underneath
+        if types.iter().all(|t| matches!(t.as_str(), "gz" | "gzip" | "zst" | "zstd" | "bz2")) {
+            rest = head;
+            continue;
+        }
+        return types;
+    }
+}
+
+/// Accepts `value` if any declaration of `key` allows its type.
+fn check_value(key: &str, value: &Value, options: &[&ConfigOption]) -> Result<(), String> {
+    if value.is_null() {
+        return Ok(());
+    }
+
+    let types: Vec<OptionType> = options.iter().flat_map(|option| option.types.iter().copied()).collect();
+    if !types.iter().any(|t| t.matches(value)) {
+        let mut expected: Vec<&str> = types.iter().map(|t| t.name()).collect();
+        expected.dedup();
+        return Err(format!("parser_config.{} must be {}, got {}", key, expected.join(" or "), json_type(value)));
+    }
+
+    if let (true, Some(pattern)) = (types.contains(&OptionType::Regex), value.as_str()) {
+        Regex::new(pattern).map_err(|e| format!("invalid regex in parser_config.{}: {}", key, e))?;
+    }
+    Ok(())
+}
+
+/// The known option within two edits of `key`, if any.
+fn closest_option<'a>(key: &str, schema: &'a [ConfigOption]) -> Option<&'a str> {
+    schema.iter()
+        .map(|option| (edit_distance(key, option.name), option.name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
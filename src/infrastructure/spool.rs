@@ -0,0 +1,28 @@
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A temporary file for content that has to be read out of order, removed when dropped.
+pub(crate) struct SpoolFile {
+    path: PathBuf,
+}
+
+impl SpoolFile {
+    /// Reserves a unique path in the system temp directory; the file itself is created by the caller.
+    pub(crate) fn new(extension: &str) -> Self {
+        Self { path: std::env::temp_dir().join(format!("ingestion-{}.{}", uuid::Uuid::new_v4(), extension)) }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SpoolFile {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => {},
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => warn!("Failed to remove spool file {}: {}", self.path.display(), e),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use apache_avro::{types::{Record, Value}, Decimal, Schema, Writer};
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use crate::infrastructure::parsers::avro_parser::{parse_avro, parse_avro_with_config, stream_avro_with_config};
+
+    const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Order",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "note", "type": ["null", "string"]},
+            {"name": "created_at", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+            {"name": "amount", "type": {"type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 2}},
+            {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["NEW", "SHIPPED"]}},
+            {"name": "shipping", "type": {"type": "record", "name": "Address", "fields": [{"name": "city", "type": "string"}]}},
+            {"name": "billing", "type": ["null", "Address"]},
+            {"name": "tags", "type": {"type": "array", "items": "string"}}
+        ]
+    }"#;
+
+    fn build_avro(count: i64) -> Vec<u8> {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut writer = Writer::new(&schema, Vec::new());
+
+        for id in 1..=count {
+            let mut record = Record::new(&schema).unwrap();
+            record.put("id", id);
+            record.put("note", if id == 1 { Value::Union(1, Box::new(Value::String("rush".into()))) } else { Value::Union(0, Box::new(Value::Null)) });
+            record.put("created_at", Value::TimestampMillis(1_700_000_000_123));
+            record.put("amount", Value::Decimal(Decimal::from((-1049i32).to_be_bytes().to_vec())));
+            record.put("status", Value::Enum(1, "SHIPPED".into()));
+            record.put("shipping", Value::Record(vec![("city".into(), Value::String("Paris".into()))]));
+            record.put("billing", Value::Union(1, Box::new(Value::Record(vec![("city".into(), Value::String("Lyon".into()))]))));
+            record.put("tags", Value::Array(vec![Value::String("a".into())]));
+            writer.append(record).unwrap();
+        }
+
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_avro_record_conversion() {
+        let result = parse_avro(&build_avro(2)).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], json!({
+            "id": 1,
+            "note": "rush",
+            "created_at": "2023-11-14T22:13:20.123Z",
+            "amount": -10.49,
+            "status": "SHIPPED",
+            "shipping": {"city": "Paris"},
+            "billing": {"city": "Lyon"},
+            "tags": ["a"]
+        }));
+        assert_eq!(result[1]["note"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_avro_stream_batches() {
+        let stream = Box::pin(std::io::Cursor::new(build_avro(3)));
+        let batches: Vec<_> = stream_avro_with_config(stream, None, 2).collect().await;
+
+        let sizes: Vec<usize> = batches.iter().map(|b| b.as_ref().unwrap().len()).collect();
+        assert_eq!(sizes, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_avro_invalid_header() {
+        assert!(parse_avro(b"not avro").is_err());
+    }
+
+    #[test]
+    fn test_avro_wide_decimals_are_decoded() {
+        let schema = Schema::parse_str(r#"{
+            "type": "record",
+            "name": "Ledger",
+            "fields": [
+                {"name": "total", "type": {"type": "fixed", "name": "Wide", "size": 20, "logicalType": "decimal", "precision": 40, "scale": 4}}
+            ]
+        }"#).unwrap();
+        let mut unscaled = vec![0xFF; 4];
+        unscaled.extend_from_slice(&(-1_234_567_890_123_456_789_012_345_678_901_234i128).to_be_bytes());
+        let mut writer = Writer::new(&schema, Vec::new());
+        let mut record = Record::new(&schema).unwrap();
+        record.put("total", Value::Decimal(Decimal::from(unscaled)));
+        writer.append(record).unwrap();
+
+        let file = writer.into_inner().unwrap();
+
+        assert_eq!(parse_avro(&file).unwrap()[0]["total"], -1.2345678901234568e29);
+        let exact = parse_avro_with_config(&file, Some(&serde_json::json!({"decimals_as_string": true}))).unwrap();
+        assert_eq!(exact[0]["total"], "-123456789012345678901234567890.1234");
+    }
+}
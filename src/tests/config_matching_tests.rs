@@ -1,40 +1,29 @@
 #[cfg(test)]
 mod tests {
-    use crate::domain::models::IngestionConfigRule;
-    use regex::Regex;
+    use crate::domain::{error::IngestionError, models::{FileMetadata, FileToProcess, IngestionConfigRule}, rule_matching::{FanoutMode, RuleMatcher}};
+    use chrono::{Duration, Utc};
     use serde_json::json;
 
     fn create_test_rules() -> Vec<IngestionConfigRule> {
         vec![
+            IngestionConfigRule::new(".*\\.csv$", "csv_data"),
             IngestionConfigRule {
-                pattern: ".*\\.csv$".to_string(),
-                target_table: "csv_data".to_string(),
-                parser_config: None,
-            },
-            IngestionConfigRule {
-                pattern: ".*test_no_headers\\.csv$".to_string(),
-                target_table: "csv_no_headers_data".to_string(),
                 parser_config: Some(json!({"headers": ["name", "age", "email", "city"]})),
+                ..IngestionConfigRule::new(".*test_no_headers\\.csv$", "csv_no_headers_data")
             },
-            IngestionConfigRule {
-                pattern: "reports/.*\\.xlsx$".to_string(),
-                target_table: "excel_reports".to_string(),
-                parser_config: None,
-            },
+            IngestionConfigRule::new("reports/.*\\.xlsx$", "excel_reports"),
         ]
     }
 
-    fn find_best_match<'a>(s3_key: &str, rules: &'a [IngestionConfigRule]) -> Option<&'a IngestionConfigRule> {
-        let matching_rules: Vec<_> = rules
-            .iter()
-            .filter(|rule| {
-                Regex::new(&rule.pattern).unwrap().is_match(s3_key)
-            })
-            .collect();
+    fn find_best_match(s3_key: &str, rules: &[IngestionConfigRule]) -> Option<IngestionConfigRule> {
+        RuleMatcher::new(rules.to_vec()).unwrap().select(&object("bucket", s3_key), &FileMetadata::default()).cloned()
+    }
 
-        matching_rules
-            .into_iter()
-            .max_by_key(|rule| rule.pattern.len())
+    fn object(bucket: &str, key: &str) -> FileToProcess {
+        FileToProcess {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        }
     }
 
     #[test]
@@ -70,4 +59,236 @@ mod tests {
         
         assert_eq!(result.target_table, "excel_reports");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_priority_beats_specificity() {
+        let mut rules = create_test_rules();
+        rules.push(IngestionConfigRule { priority: 10, ..IngestionConfigRule::new(".*", "catch_all") });
+
+        let result = find_best_match("data/test_no_headers.csv", &rules).unwrap();
+
+        assert_eq!(result.target_table, "catch_all");
+    }
+
+    #[test]
+    fn test_ties_are_independent_of_storage_order() {
+        let rules = vec![
+            IngestionConfigRule { priority: 1, ..IngestionConfigRule::new("^data/", "b_table") },
+            IngestionConfigRule { priority: 1, ..IngestionConfigRule::new("^data/", "a_table") },
+            IngestionConfigRule { priority: 1, ..IngestionConfigRule::new("\\.csv$", "csv") },
+        ];
+        let mut reversed = rules.clone();
+        reversed.reverse();
+
+        assert_eq!(find_best_match("data/x.csv", &rules).unwrap().target_table, "csv");
+        assert_eq!(find_best_match("data/x.csv", &reversed).unwrap().target_table, "csv");
+        assert_eq!(find_best_match("data/x.txt", &rules).unwrap().target_table, "a_table");
+        assert_eq!(find_best_match("data/x.txt", &reversed).unwrap().target_table, "a_table");
+    }
+
+    #[test]
+    fn test_matching_lists_rules_in_precedence_order() {
+        let mut rules = create_test_rules();
+        rules.push(IngestionConfigRule { priority: -1, ..IngestionConfigRule::new("^data/", "data") });
+        let matcher = RuleMatcher::new(rules).unwrap();
+
+        let tables: Vec<&str> = matcher.matching(&object("bucket", "data/test_no_headers.csv"), &FileMetadata::default()).iter().map(|r| r.target_table.as_str()).collect();
+
+        assert_eq!(tables, vec!["csv_no_headers_data", "csv_data", "data"]);
+        assert!(matcher.matching(&object("bucket", "other/file.txt"), &FileMetadata::default()).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_a_config_error() {
+        let result = RuleMatcher::new(vec![IngestionConfigRule::new("data/(.*", "broken")]);
+
+        assert!(matches!(result, Err(IngestionError::Config(_))));
+    }
+
+    #[test]
+    fn test_priority_defaults_to_zero() {
+        let rule: IngestionConfigRule = serde_json::from_value(json!({
+            "pattern": ".*", "target_table": "t", "parser_config": null, "file_type": null
+        })).unwrap();
+
+        assert_eq!(rule.priority, 0);
+    }
+
+    #[test]
+    fn test_fanout_targets_follow_the_best_rule() {
+        let archive = IngestionConfigRule { priority: -5, fanout: true, ..IngestionConfigRule::new("^data/", "archive") };
+        let mut rules = create_test_rules();
+        rules.push(archive);
+        let matcher = RuleMatcher::new(rules).unwrap();
+
+        let flagged: Vec<&str> = matcher.select_targets(&object("bucket", "data/test_no_headers.csv"), &FileMetadata::default(), FanoutMode::Flagged).iter().map(|r| r.target_table.as_str()).collect();
+        let all: Vec<&str> = matcher.select_targets(&object("bucket", "data/test_no_headers.csv"), &FileMetadata::default(), FanoutMode::All).iter().map(|r| r.target_table.as_str()).collect();
+
+        assert_eq!(flagged, vec!["csv_no_headers_data", "archive"]);
+        assert_eq!(all, vec!["csv_no_headers_data", "csv_data", "archive"]);
+        assert!(matcher.select_targets(&object("bucket", "other/file.txt"), &FileMetadata::default(), FanoutMode::All).is_empty());
+        assert_eq!("ALL".parse::<FanoutMode>().unwrap(), FanoutMode::All);
+        assert!("some".parse::<FanoutMode>().is_err());
+    }
+
+    #[test]
+    fn test_fanout_skips_a_target_table_that_is_already_targeted() {
+        let rules = vec![
+            IngestionConfigRule::new(".*\\.csv$", "csv_data"),
+            IngestionConfigRule { priority: -5, fanout: true, ..IngestionConfigRule::new("^data/", "csv_data") },
+            IngestionConfigRule { priority: -10, fanout: true, ..IngestionConfigRule::new("^data/", "archive") },
+            IngestionConfigRule { priority: -20, fanout: true, ..IngestionConfigRule::new(".*", "archive") },
+        ];
+        let matcher = RuleMatcher::new(rules).unwrap();
+
+        let targets = matcher.select_targets(&object("bucket", "data/orders.csv"), &FileMetadata::default(), FanoutMode::Flagged);
+        let patterns: Vec<(&str, &str)> = targets.iter().map(|r| (r.pattern.as_str(), r.target_table.as_str())).collect();
+
+        assert_eq!(patterns, vec![(".*\\.csv$", "csv_data"), ("^data/", "archive")]);
+    }
+
+    #[test]
+    fn test_bucket_and_metadata_matchers() {
+        let mut eu = IngestionConfigRule::new("orders\\.csv$", "orders_eu");
+        eu.matchers.bucket = Some("^raw-eu$".to_string());
+        let mut large = IngestionConfigRule { priority: 5, ..IngestionConfigRule::new("orders\\.csv$", "orders_bulk") };
+        large.matchers.min_size = Some(1_000_000);
+        let mut tagged = IngestionConfigRule { priority: 10, ..IngestionConfigRule::new("orders\\.csv$", "orders_pii") };
+        tagged.matchers.tags.insert("classification".to_string(), "pii".to_string());
+        tagged.matchers.content_type = Some("^text/csv".to_string());
+        let matcher = RuleMatcher::new(vec![eu, large, tagged, IngestionConfigRule { priority: -1, ..IngestionConfigRule::new("orders\\.csv$", "orders") }]).unwrap();
+
+        let small = FileMetadata { size: Some(10), ..Default::default() };
+        let big = FileMetadata { size: Some(5_000_000), ..Default::default() };
+        let mut pii = FileMetadata { content_type: Some("text/csv; charset=utf-8".to_string()), ..Default::default() };
+        pii.tags.insert("classification".to_string(), "pii".to_string());
+
+        let table = |bucket: &str, metadata: &FileMetadata| matcher.select(&object(bucket, "in/orders.csv"), metadata).unwrap().target_table.clone();
+        assert_eq!(table("raw-eu", &small), "orders_eu");
+        assert_eq!(table("raw-us", &small), "orders");
+        assert_eq!(table("raw-us", &big), "orders_bulk");
+        assert_eq!(table("raw-us", &pii), "orders_pii");
+        // Size conditions do not match when the size is unknown
+        assert_eq!(table("raw-us", &FileMetadata::default()), "orders");
+    }
+
+    #[test]
+    fn test_metadata_matcher_keys_ignore_case() {
+        let mut erp = IngestionConfigRule::new("orders\\.csv$", "orders_erp");
+        erp.matchers.metadata.insert("Source-System".to_string(), "ERP".to_string());
+        let matcher = RuleMatcher::new(vec![erp]).unwrap();
+        assert!(!matcher.uses_tags());
+
+        let mut metadata = FileMetadata::default();
+        metadata.user_metadata.insert("source-system".to_string(), "ERP".to_string());
+        assert!(matcher.select(&object("raw", "orders.csv"), &metadata).is_some());
+
+        metadata.user_metadata.insert("source-system".to_string(), "erp".to_string());
+        assert!(matcher.select(&object("raw", "orders.csv"), &metadata).is_none());
+    }
+
+    #[test]
+    fn test_invalid_matcher_regex_is_a_config_error() {
+        let mut broken = IngestionConfigRule::new(".*", "broken");
+        broken.matchers.bucket = Some("raw-(eu".to_string());
+
+        assert!(matches!(RuleMatcher::new(vec![broken]), Err(IngestionError::Config(_))));
+    }
+
+    #[test]
+    fn test_matchers_deserialize_with_defaults() {
+        let rule: IngestionConfigRule = serde_json::from_value(json!({
+            "pattern": ".*", "target_table": "t", "parser_config": null, "file_type": null,
+            "matchers": {"bucket": "^raw-eu$", "metadata": {"source": "erp"}, "max_size": 1024}
+        })).unwrap();
+
+        assert_eq!(rule.matchers.bucket.as_deref(), Some("^raw-eu$"));
+        assert_eq!(rule.matchers.metadata.get("source").map(String::as_str), Some("erp"));
+        assert_eq!(rule.matchers.max_size, Some(1024));
+        assert!(rule.matchers.tags.is_empty());
+    }
+
+    fn version(rule_id: &str, version: u32, target_table: &str) -> IngestionConfigRule {
+        let mut rule = IngestionConfigRule::new("^orders/", target_table);
+        rule.rule_id = Some(rule_id.to_string());
+        rule.version = version;
+        rule
+    }
+
+    fn table_for(rules: Vec<IngestionConfigRule>) -> Option<String> {
+        find_best_match("orders/today.csv", &rules).map(|rule| rule.target_table)
+    }
+
+    #[test]
+    fn test_highest_version_in_effect_applies() {
+        let now = Utc::now();
+        let mut staged = version("orders", 3, "orders_v3");
+        staged.valid_from = Some(now + Duration::days(1));
+        let mut expired = version("orders", 4, "orders_v4");
+        expired.valid_until = Some(now - Duration::days(1));
+
+        let rules = vec![version("orders", 1, "orders_v1"), staged, version("orders", 2, "orders_v2"), expired];
+
+        assert_eq!(table_for(rules), Some("orders_v2".to_string()));
+    }
+
+    #[test]
+    fn test_disabled_latest_version_switches_the_rule_off() {
+        let mut disabled = version("orders", 2, "orders_v2");
+        disabled.enabled = false;
+
+        assert_eq!(table_for(vec![version("orders", 1, "orders_v1"), disabled.clone()]), None);
+        // Rules without an id are independent of each other
+        let mut unversioned = disabled;
+        unversioned.rule_id = None;
+        assert_eq!(table_for(vec![version("orders", 1, "orders_v1"), unversioned]), Some("orders_v1".to_string()));
+    }
+
+    #[test]
+    fn test_duplicate_version_is_a_config_error() {
+        let result = RuleMatcher::new(vec![version("orders", 1, "a"), version("orders", 1, "b")]);
+
+        match result {
+            Err(IngestionError::Config(message)) => assert_eq!(message, "Rule 'orders' has version 1 more than once"),
+            _ => panic!("expected a config error"),
+        }
+    }
+
+    #[test]
+    fn test_versioning_fields_deserialize_with_defaults() {
+        let legacy: IngestionConfigRule = serde_json::from_value(json!({
+            "pattern": ".*", "target_table": "t", "parser_config": null, "file_type": null
+        })).unwrap();
+        assert_eq!((legacy.rule_id, legacy.version, legacy.enabled), (None, 1, true));
+
+        let staged: IngestionConfigRule = serde_json::from_value(json!({
+            "pattern": ".*", "target_table": "t", "parser_config": null, "file_type": null,
+            "rule_id": "orders", "version": 2, "valid_from": "2030-01-01T00:00:00Z"
+        })).unwrap();
+        assert_eq!(staged.version, 2);
+        assert!(!staged.is_in_effect(Utc::now()));
+    }
+
+    #[test]
+    fn test_validity_accepts_bson_dates() {
+        use mongodb::bson::{doc, Bson, DateTime as BsonDateTime};
+
+        let start = BsonDateTime::from_millis(1_893_456_000_000);
+        let stored = doc! {
+            "pattern": ".*", "target_table": "t", "parser_config": Bson::Null, "file_type": Bson::Null,
+            "valid_from": start, "valid_until": "2031-01-01T00:00:00Z",
+        };
+        let expected = chrono::DateTime::from_timestamp_millis(start.timestamp_millis());
+
+        let from_driver: IngestionConfigRule = mongodb::bson::from_document(stored.clone()).unwrap();
+        assert_eq!(from_driver.valid_from, expected);
+        let relaxed: IngestionConfigRule = serde_json::from_value(Bson::Document(stored.clone()).into_relaxed_extjson()).unwrap();
+        assert_eq!(relaxed.valid_from, expected);
+        let canonical: IngestionConfigRule = serde_json::from_value(Bson::Document(stored).into_canonical_extjson()).unwrap();
+        assert_eq!(canonical.valid_from, expected);
+        assert!(canonical.valid_until.is_some());
+
+        let invalid = serde_json::from_value::<IngestionConfigRule>(json!({"pattern": ".*", "target_table": "t", "parser_config": null, "file_type": null, "valid_from": 5}));
+        assert!(invalid.unwrap_err().to_string().contains("expected an RFC 3339 timestamp string or a BSON date"));
+    }
+}
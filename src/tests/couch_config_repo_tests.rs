@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::infrastructure::couchdb::config_repo::CouchConfigRepository;
+
+    fn repo() -> CouchConfigRepository {
+        CouchConfigRepository::new("http://localhost:5984".to_string(), "ingestion_rules".to_string())
+    }
+
+    #[test]
+    fn test_design_documents_are_not_rules() {
+        let result = json!({"total_rows": 2, "offset": 0, "rows": [
+            {"id": "_design/rules", "key": "_design/rules", "value": {"rev": "1-a"}, "doc": {
+                "_id": "_design/rules", "_rev": "1-a", "views": {"by_table": {"map": "function (doc) { emit(doc.target_table); }"}}
+            }},
+            {"id": "csv", "key": "csv", "value": {"rev": "1-b"}, "doc": {
+                "_id": "csv", "_rev": "1-b", "pattern": ".*\\.csv$", "target_table": "orders"
+            }}
+        ]});
+
+        let rules = repo().rules_from_all_docs(&result).unwrap();
+
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].target_table, "orders");
+    }
+
+    #[test]
+    fn test_invalid_rule_documents_still_fail() {
+        let result = json!({"rows": [{"id": "broken", "doc": {"_id": "broken", "target_table": "orders"}}]});
+
+        assert!(repo().rules_from_all_docs(&result).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::infrastructure::parsers::csv_parser::{parse_csv, parse_csv_with_config};
+    use crate::infrastructure::parsers::csv_parser::{parse_csv, parse_csv_with_config, stream_csv_with_config};
+    use futures_util::StreamExt;
     use serde_json::json;
 
     #[test]
@@ -36,4 +37,183 @@ mod tests {
         assert_eq!(result[0]["column_2"], "john@test.com");
         assert_eq!(result[0]["column_3"], "extra");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_csv_stream_yields_bounded_batches() {
+        let csv_data = b"name,age\nJohn,25\nJane,30\nBob,41".to_vec();
+        let stream = Box::pin(std::io::Cursor::new(csv_data));
+        let batches: Vec<_> = stream_csv_with_config(stream, None, 2).collect().await;
+
+        assert_eq!(batches.len(), 2);
+        let first = batches[0].as_ref().unwrap();
+        let second = batches[1].as_ref().unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 1);
+        assert_eq!(first[0]["name"], "John");
+        assert_eq!(second[0]["age"], "41");
+    }
+
+    #[tokio::test]
+    async fn test_csv_stream_reports_malformed_row() {
+        let csv_data = b"name,age\nJohn,25\nJane,30,extra".to_vec();
+        let stream = Box::pin(std::io::Cursor::new(csv_data));
+        let batches: Vec<_> = stream_csv_with_config(stream, None, 10).collect().await;
+
+        assert_eq!(batches.len(), 1);
+        assert!(batches[0].is_err());
+    }
+
+    #[test]
+    fn test_csv_typed_columns() {
+        let csv_data = b"name,age,score,active,born,price\nJohn,25,1.5,yes,03/02/1990,10.456\nJane,,2,false,15/07/1985,3";
+        let config = json!({
+            "columns": {
+                "age": "integer",
+                "score": "float",
+                "active": "boolean",
+                "born": {"type": "date", "format": "%d/%m/%Y"},
+                "price": {"type": "decimal", "scale": 2}
+            }
+        });
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result[0]["name"], "John");
+        assert_eq!(result[0]["age"], 25);
+        assert_eq!(result[0]["score"], 1.5);
+        assert_eq!(result[0]["active"], true);
+        assert_eq!(result[0]["born"], "1990-02-03");
+        assert_eq!(result[0]["price"], 10.46);
+        assert_eq!(result[1]["age"], serde_json::Value::Null);
+        assert_eq!(result[1]["active"], false);
+        assert_eq!(result[1]["price"], 3);
+    }
+
+    #[test]
+    fn test_csv_decimals_as_string_keep_every_digit() {
+        let csv_data = b"price\n12345678901234567.89\n-0.1";
+        let config = json!({"columns": {"price": {"type": "decimal", "scale": 2, "as_string": true}}});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result[0]["price"], "12345678901234567.89");
+        assert_eq!(result[1]["price"], "-0.10");
+    }
+
+    #[test]
+    fn test_csv_coercion_error_reports_row_and_column() {
+        let csv_data = b"name,age\nJohn,25\nJane,abc";
+        let config = json!({"columns": {"age": "integer"}});
+        let err = parse_csv_with_config(csv_data, Some(&config)).unwrap_err();
+
+        assert_eq!(err.to_string(), "Parsing error: CSV row 2, column 'age': cannot convert 'abc' to integer");
+    }
+
+    #[test]
+    fn test_csv_null_values() {
+        let csv_data = b"name,age\nJohn,NA\nJane,30";
+        let config = json!({"columns": {"age": "integer"}, "null_values": ["NA"]});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result[0]["age"], serde_json::Value::Null);
+        assert_eq!(result[1]["age"], 30);
+    }
+
+    #[test]
+    fn test_csv_infer_types() {
+        let csv_data = b"name,age,ratio,active,joined\nJohn,25,0.5,true,2024-01-02\nJane,30,1,FALSE,2023-12-31";
+        let config = json!({"infer_types": true, "columns": {"name": "string"}});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result[0]["name"], "John");
+        assert_eq!(result[0]["age"], 25);
+        assert_eq!(result[1]["ratio"], 1.0);
+        assert_eq!(result[1]["active"], false);
+        assert_eq!(result[0]["joined"], "2024-01-02");
+    }
+
+    #[test]
+    fn test_csv_unknown_column_type_is_config_error() {
+        let config = json!({"columns": {"age": "bigint"}});
+        let err = parse_csv_with_config(b"age\n1", Some(&config)).unwrap_err();
+
+        assert!(matches!(err, crate::domain::error::IngestionError::Config(_)));
+    }
+
+    #[test]
+    fn test_csv_delimiter_and_trim() {
+        let csv_data = b"name | city\nJohn | Paris\nJane | Berlin";
+        let config = json!({"delimiter": "|", "trim": true});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["name"], "John");
+        assert_eq!(result[1]["city"], "Berlin");
+    }
+
+    #[test]
+    fn test_csv_european_export() {
+        let csv_data = b"Export generated 2024-01-01\n\nproduct;price\n# discontinued items removed\n'Widget; large';12,50";
+        let config = json!({
+            "delimiter": ";",
+            "quote": "'",
+            "comment": "#",
+            "skip_rows": 2,
+            "columns": {"price": {"type": "float", "decimal_separator": ","}}
+        });
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0]["product"], "Widget; large");
+        assert_eq!(result[0]["price"], 12.5);
+    }
+
+    #[test]
+    fn test_csv_tab_delimited_with_escape() {
+        let csv_data = b"name\tquote\nJohn\t\"say \\\"hi\\\"\"";
+        let config = json!({"delimiter": "tab", "escape": "\\", "double_quote": false});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result[0]["quote"], "say \"hi\"");
+    }
+
+    #[test]
+    fn test_csv_no_headers_uses_column_names() {
+        let csv_data = b"John,25\nJane,30";
+        let config = json!({"has_headers": false});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["column_0"], "John");
+        assert_eq!(result[1]["column_1"], "30");
+    }
+
+    #[test]
+    fn test_csv_custom_headers_replace_header_row() {
+        let csv_data = b"n,a\nJohn,25";
+        let config = json!({"headers": ["name", "age"], "has_headers": true});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0]["name"], "John");
+    }
+
+    #[test]
+    fn test_csv_flexible_rows() {
+        let csv_data = b"name,age\nJohn\nJane,30,extra";
+        assert!(parse_csv(csv_data).is_err());
+
+        let config = json!({"flexible": true});
+        let result = parse_csv_with_config(csv_data, Some(&config)).unwrap();
+
+        assert_eq!(result[0]["name"], "John");
+        assert!(result[0].get("age").is_none());
+        assert_eq!(result[1]["column_2"], "extra");
+    }
+
+    #[test]
+    fn test_csv_invalid_delimiter_is_config_error() {
+        let config = json!({"delimiter": ";;"});
+        let err = parse_csv_with_config(b"a;b", Some(&config)).unwrap_err();
+
+        assert!(matches!(err, crate::domain::error::IngestionError::Config(_)));
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+    use async_compression::tokio::bufread::{BzEncoder, GzipEncoder, ZstdEncoder};
+    use tokio::io::AsyncReadExt;
+    use zip::write::SimpleFileOptions;
+    use futures_util::StreamExt;
+    use crate::domain::ports::{ArchiveEntries, Decompressor, FileStream, OpenedFile};
+    use crate::infrastructure::decompression_adapter::DecompressionAdapter;
+
+    const CSV: &[u8] = b"name,age\nJohn,25\nJane,30\n";
+
+    fn stream(bytes: Vec<u8>) -> FileStream {
+        Box::pin(Cursor::new(bytes))
+    }
+
+    async fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        GzipEncoder::new(bytes).read_to_end(&mut out).await.unwrap();
+        out
+    }
+
+    fn zip_archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer.add_directory("nested/", SimpleFileOptions::default()).unwrap();
+        for (name, content) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    async fn open_single(key: &str, bytes: Vec<u8>) -> (String, Vec<u8>) {
+        match DecompressionAdapter::new().open(key, stream(bytes)).await.unwrap() {
+            OpenedFile::Single { key, mut stream } => {
+                let mut content = Vec::new();
+                stream.read_to_end(&mut content).await.unwrap();
+                (key, content)
+            },
+            OpenedFile::Archive(_) => panic!("expected a single file"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gzip_is_detected_by_extension() {
+        let (key, content) = open_single("data/people.csv.gz", gzip(CSV).await).await;
+
+        assert_eq!(key, "data/people.csv");
+        assert_eq!(content, CSV);
+    }
+
+    #[tokio::test]
+    async fn test_zstd_and_bzip2_are_decompressed() {
+        let mut zstd = Vec::new();
+        ZstdEncoder::new(CSV).read_to_end(&mut zstd).await.unwrap();
+        let mut bzip2 = Vec::new();
+        BzEncoder::new(CSV).read_to_end(&mut bzip2).await.unwrap();
+
+        assert_eq!(open_single("people.csv.zst", zstd).await, ("people.csv".to_string(), CSV.to_vec()));
+        assert_eq!(open_single("people.csv.bz2", bzip2).await, ("people.csv".to_string(), CSV.to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_gzip_is_detected_by_magic_bytes() {
+        let (key, content) = open_single("data/people.csv", gzip(CSV).await).await;
+
+        assert_eq!(key, "data/people.csv");
+        assert_eq!(content, CSV);
+    }
+
+    #[tokio::test]
+    async fn test_plain_file_is_passed_through() {
+        let (key, content) = open_single("data/people.csv", CSV.to_vec()).await;
+
+        assert_eq!(key, "data/people.csv");
+        assert_eq!(content, CSV);
+    }
+
+    #[tokio::test]
+    async fn test_xlsx_container_is_not_treated_as_archive() {
+        let bytes = zip_archive(&[("xl/workbook.xml", b"<workbook/>")]);
+
+        let (key, content) = open_single("report.xlsx", bytes.clone()).await;
+
+        assert_eq!(key, "report.xlsx");
+        assert_eq!(content, bytes);
+    }
+
+    #[tokio::test]
+    async fn test_zip_entries_are_extracted() {
+        let bytes = zip_archive(&[("people.csv", CSV), ("nested/items.json", b"[]"), ("__MACOSX/._people.csv", b"junk")]);
+
+        let opened = DecompressionAdapter::new().open("batch.zip", stream(bytes)).await.unwrap();
+
+        let OpenedFile::Archive(entries) = opened else { panic!("expected an archive") };
+        let entries = read_entries(entries).await;
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["people.csv", "nested/items.json"]);
+        assert_eq!(entries[0].1.as_ref().unwrap(), CSV);
+    }
+
+    /// Reads every entry to the end, keeping the name and content or error of each.
+    async fn read_entries(mut entries: ArchiveEntries) -> Vec<(String, Result<Vec<u8>, String>)> {
+        let mut read = Vec::new();
+        while let Some(entry) = entries.next().await {
+            match entry {
+                Ok(mut entry) => {
+                    let mut content = Vec::new();
+                    let result = entry.stream.read_to_end(&mut content).await.map(|_| content).map_err(|e| e.to_string());
+                    read.push((entry.name, result));
+                },
+                Err(e) => read.push(("<archive>".to_string(), Err(e.to_string()))),
+            }
+        }
+        read
+    }
+
+    #[tokio::test]
+    async fn test_zip_entries_over_the_limits_fail() {
+        let bytes = zip_archive(&[("small.csv", CSV), ("big.csv", &[b'x'; 100]), ("after.csv", CSV), ("last.csv", CSV)]);
+        let adapter = DecompressionAdapter::new().with_archive_limits(50, 60);
+
+        let OpenedFile::Archive(entries) = adapter.open("batch.zip", stream(bytes)).await.unwrap() else { panic!("expected an archive") };
+        let entries = read_entries(entries).await;
+
+        assert_eq!(entries[0], ("small.csv".to_string(), Ok(CSV.to_vec())));
+        assert_eq!(entries[1].0, "<archive>");
+        assert!(entries[1].1.as_ref().unwrap_err().contains("big.csv: 100 bytes uncompressed, more than the limit of 50"), "{:?}", entries[1]);
+        assert_eq!(entries[2], ("after.csv".to_string(), Ok(CSV.to_vec())));
+        assert_eq!(entries[3].0, "last.csv");
+        assert!(entries[3].1.as_ref().unwrap_err().contains("archive inflates to more than the limit of 60 bytes"), "{:?}", entries[3]);
+        assert!(entries[4].1.as_ref().unwrap_err().contains("entries from last.csv on were not ingested"), "{:?}", entries[4]);
+        assert_eq!(entries.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_unread_zip_entries_do_not_block_the_next() {
+        let bytes = zip_archive(&[("first.csv", &[b'x'; 200_000]), ("second.csv", CSV)]);
+
+        let OpenedFile::Archive(mut entries) = DecompressionAdapter::new().open("batch.zip", stream(bytes)).await.unwrap() else { panic!("expected an archive") };
+        let first = entries.next().await.unwrap().unwrap();
+        drop(first);
+        let mut second = entries.next().await.unwrap().unwrap();
+
+        let mut content = Vec::new();
+        second.stream.read_to_end(&mut content).await.unwrap();
+        assert_eq!(second.name, "second.csv");
+        assert_eq!(content, CSV);
+        assert!(entries.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_gzip_fails_on_read() {
+        let (_, mut stream) = match DecompressionAdapter::new().open("people.csv.gz", stream(b"not gzip".to_vec())).await.unwrap() {
+            OpenedFile::Single { key, stream } => (key, stream),
+            OpenedFile::Archive(_) => panic!("expected a single file"),
+        };
+
+        let mut content = Vec::new();
+        assert!(stream.read_to_end(&mut content).await.is_err());
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::domain::{document_schema::DocumentSchema, error::IngestionError};
+
+    #[test]
+    fn test_missing_schema_accepts_everything() {
+        let schema = DocumentSchema::from_config(None).unwrap();
+
+        assert!(schema.validate(&json!({"anything": [1, 2]})).is_empty());
+    }
+
+    #[test]
+    fn test_errors_name_the_offending_value() {
+        let schema = DocumentSchema::from_config(Some(&json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer"},
+                "email": {"type": "string", "pattern": "@"}
+            }
+        }))).unwrap();
+
+        assert!(schema.validate(&json!({"id": 1, "email": "a@b.c"})).is_empty());
+
+        let errors = schema.validate(&json!({"email": "nobody"}));
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("/: ") && e.contains("\"id\" is a required property")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("/email: ")), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_invalid_schema_is_a_config_error() {
+        match DocumentSchema::from_config(Some(&json!({"type": "whole number"}))) {
+            Err(IngestionError::Config(message)) => assert!(message.starts_with("invalid schema: "), "{}", message),
+            Err(other) => panic!("expected a config error, got {:?}", other),
+            Ok(_) => panic!("expected a config error"),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::infrastructure::parsers::{
+        csv_parser::parse_csv_with_config,
+        encoding::detect_encoding,
+        fixed_width_parser::parse_fixed_width_with_config,
+        json_parser::parse_json,
+        ndjson_parser::{parse_ndjson, parse_ndjson_with_config},
+        txt_parser::{parse_txt, parse_txt_with_config},
+    };
+
+    fn utf16(text: &str, little_endian: bool, bom: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if bom {
+            bytes.extend(if little_endian { [0xff, 0xfe] } else { [0xfe, 0xff] });
+        }
+        for unit in text.encode_utf16() {
+            bytes.extend(if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() });
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_detects_encodings() {
+        assert_eq!(detect_encoding(b"plain ascii").name(), "UTF-8");
+        assert_eq!(detect_encoding("caf\u{e9}".as_bytes()).name(), "UTF-8");
+        assert_eq!(detect_encoding(b"caf\xe9").name(), "windows-1252");
+        assert_eq!(detect_encoding(&utf16("name,age", true, false)).name(), "UTF-16LE");
+        assert_eq!(detect_encoding(&utf16("name,age", false, false)).name(), "UTF-16BE");
+        assert_eq!(detect_encoding(&utf16("x", false, true)).name(), "UTF-16BE");
+    }
+
+    #[test]
+    fn test_latin1_csv_is_auto_detected() {
+        let result = parse_csv_with_config(b"name,city\nRen\xe9,Z\xfcrich\n", None).unwrap();
+
+        assert_eq!(result[0]["name"], "René");
+        assert_eq!(result[0]["city"], "Zürich");
+    }
+
+    #[test]
+    fn test_explicit_encoding_is_used() {
+        // 0x80 is the euro sign in Windows-1252
+        let config = json!({"encoding": "windows-1252"});
+
+        let result = parse_txt_with_config(b"price: 5\x80", Some(&config)).unwrap();
+
+        assert_eq!(result[0]["content"], "price: 5€");
+    }
+
+    #[test]
+    fn test_utf16_with_bom() {
+        let result = parse_txt(&utf16("first\r\nsecond ✓\r\n", true, true)).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["content"], "first");
+        assert_eq!(result[1]["content"], "second ✓");
+
+        let csv = parse_csv_with_config(&utf16("name,age\nJohn,25\n", false, true), None).unwrap();
+        assert_eq!(csv[0]["name"], "John");
+    }
+
+    #[test]
+    fn test_bom_overrides_configured_encoding() {
+        let config = json!({"encoding": "iso-8859-1"});
+
+        let result = parse_txt_with_config(&utf16("é", true, true), Some(&config)).unwrap();
+
+        assert_eq!(result[0]["content"], "é");
+    }
+
+    #[test]
+    fn test_utf8_bom_is_stripped() {
+        let csv = parse_csv_with_config(b"\xef\xbb\xbfname,age\nJohn,25\n", None).unwrap();
+        assert!(csv[0].get("name").is_some());
+
+        assert_eq!(parse_json(b"\xef\xbb\xbf[{\"a\": 1}]").unwrap()[0]["a"], 1);
+        assert_eq!(parse_ndjson(b"\xef\xbb\xbf{\"a\": 1}\n").unwrap()[0]["a"], 1);
+        assert_eq!(parse_txt(b"\xef\xbb\xbfhello").unwrap()[0]["content"], "hello");
+    }
+
+    #[test]
+    fn test_fixed_width_positions_count_decoded_characters() {
+        let config = json!({"encoding": "latin1", "fields": [
+            {"name": "name", "start": 1, "length": 5},
+            {"name": "code", "start": 6, "length": 2}
+        ]});
+
+        let result = parse_fixed_width_with_config(b"Ren\xe9 AB", Some(&config)).unwrap();
+
+        assert_eq!(result[0], json!({"name": "René", "code": "AB", "_source_row": 1}));
+    }
+
+    #[test]
+    fn test_unknown_encoding_is_a_config_error() {
+        let config = json!({"encoding": "ebcdic-klingon"});
+
+        let err = parse_txt_with_config(b"x", Some(&config)).unwrap_err();
+
+        assert_eq!(err.to_string(), "Configuration error: Unknown encoding 'ebcdic-klingon'");
+    }
+
+    #[test]
+    fn test_invalid_bytes_report_their_offset() {
+        let config = json!({"encoding": "utf-8"});
+
+        let err = parse_txt_with_config(b"caf\xc3\xa9\nna\xefve", Some(&config)).unwrap_err();
+        assert_eq!(err.to_string(), "Parsing error: Invalid UTF-8 byte sequence at offset 8");
+
+        let err = parse_json(&utf16("[1]", true, true)[..5]).unwrap_err();
+        assert_eq!(err.to_string(), "Parsing error: Invalid UTF-16LE byte sequence at offset 4");
+    }
+
+    #[test]
+    fn test_streamed_invalid_bytes_after_the_detection_sample_fail() {
+        let mut csv_data = b"name\n".to_vec();
+        csv_data.extend(b"Jane\n".repeat(2000));
+        let offset = csv_data.len() + 3;
+        csv_data.extend(b"Ren\xe9e\n");
+
+        let err = parse_csv_with_config(&csv_data, None).unwrap_err().to_string();
+        assert!(err.ends_with(&format!("Invalid UTF-8 byte sequence at offset {}", offset)), "{}", err);
+
+        let config = json!({"encoding": "utf-8", "on_error": "skip"});
+        let err = parse_ndjson_with_config(b"{\"name\": \"Ren\xe9e\"}\n", Some(&config)).unwrap_err().to_string();
+        assert!(err.ends_with("Invalid UTF-8 byte sequence at offset 13"), "{}", err);
+    }
+
+    #[test]
+    fn test_bom_wins_over_configured_utf8() {
+        let config = json!({"encoding": "utf-8"});
+
+        let result = parse_txt_with_config(&utf16("naïve", false, true), Some(&config)).unwrap();
+
+        assert_eq!(result[0]["content"], "naïve");
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use crate::infrastructure::parsers::excel_parser::{parse_excel, parse_excel_with_config};
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+    use serde_json::json;
+
+    fn build_workbook() -> Vec<u8> {
+        let mut workbook = Workbook::new();
+        let date_format = Format::new().set_num_format("yyyy-mm-dd");
+
+        let orders = workbook.add_worksheet().set_name("Orders").unwrap();
+        orders.write(0, 0, "id").unwrap();
+        orders.write(0, 1, "amount").unwrap();
+        orders.write(0, 2, "paid").unwrap();
+        orders.write(0, 3, "ordered_on").unwrap();
+        orders.write(1, 0, 1).unwrap();
+        orders.write(1, 1, 19.99).unwrap();
+        orders.write(1, 2, true).unwrap();
+        let date = ExcelDateTime::from_ymd(2024, 3, 15).unwrap();
+        orders.write_datetime_with_format(1, 3, &date, &date_format).unwrap();
+
+        let customers = workbook.add_worksheet().set_name("Customers").unwrap();
+        customers.write(0, 0, "Customer export").unwrap();
+        customers.write(2, 0, "name").unwrap();
+        customers.write(2, 1, "city").unwrap();
+        customers.write(3, 0, "John").unwrap();
+        customers.write(3, 1, "Paris").unwrap();
+
+        workbook.save_to_buffer().unwrap()
+    }
+
+    #[test]
+    fn test_excel_first_sheet_typed_cells() {
+        let result = parse_excel(&build_workbook()).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0]["id"], 1);
+        assert_eq!(result[0]["amount"], 19.99);
+        assert_eq!(result[0]["paid"], true);
+        assert_eq!(result[0]["ordered_on"], "2024-03-15");
+    }
+
+    #[test]
+    fn test_excel_sheet_by_name_with_header_row() {
+        let config = json!({"sheet_name": "Customers", "header_row": 2});
+        let result = parse_excel_with_config(&build_workbook(), Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0]["name"], "John");
+        assert_eq!(result[0]["city"], "Paris");
+    }
+
+    #[test]
+    fn test_excel_sheet_by_index() {
+        let config = json!({"sheet_index": 1, "header_row": 2});
+        let result = parse_excel_with_config(&build_workbook(), Some(&config)).unwrap();
+
+        assert_eq!(result[0]["name"], "John");
+    }
+
+    #[test]
+    fn test_excel_all_sheets_tags_sheet_name() {
+        let config = json!({"all_sheets": true});
+        let result = parse_excel_with_config(&build_workbook(), Some(&config)).unwrap();
+
+        assert_eq!(result[0]["sheet_name"], "Orders");
+        assert!(result.iter().any(|doc| doc["sheet_name"] == "Customers"));
+    }
+
+    #[test]
+    fn test_excel_unknown_sheet_name() {
+        let config = json!({"sheet_name": "Missing"});
+        assert!(parse_excel_with_config(&build_workbook(), Some(&config)).is_err());
+    }
+
+    #[test]
+    fn test_excel_sheet_index_out_of_range() {
+        let config = json!({"sheet_index": 5});
+        let err = parse_excel_with_config(&build_workbook(), Some(&config)).unwrap_err();
+
+        assert!(err.to_string().contains("No sheet at index 5, available sheets: [\"Orders\", \"Customers\"]"), "{}", err);
+    }
+}
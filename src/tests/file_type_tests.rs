@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::file_type::{detect_file_type, extension_of};
+    use crate::infrastructure::parser_adapter::ParserAdapter;
+
+    #[test]
+    fn test_extension_is_lowercased_and_taken_from_last_segment() {
+        assert_eq!(extension_of("Data/Report.CSV"), Some("csv".to_string()));
+        assert_eq!(extension_of("v1.2/readme"), None);
+        assert_eq!(extension_of("data/file."), None);
+    }
+
+    #[test]
+    fn test_magic_bytes_win_over_extension() {
+        let parser = ParserAdapter::new();
+
+        assert_eq!(detect_file_type("exports/data.csv", None, b"PAR1\x15\x04", &parser), "parquet");
+        assert_eq!(detect_file_type("scan", None, b"%PDF-1.7\n", &parser), "pdf");
+        assert_eq!(detect_file_type("events.bin", None, b"Obj\x01\x04\x16", &parser), "avro");
+    }
+
+    #[test]
+    fn test_zip_container_keeps_spreadsheet_extension() {
+        let parser = ParserAdapter::new();
+
+        assert_eq!(detect_file_type("sheet.ods", None, b"PK\x03\x04rest", &parser), "ods");
+        assert_eq!(detect_file_type("upload", None, b"PK\x03\x04rest", &parser), "xlsx");
+    }
+
+    #[test]
+    fn test_content_type_is_used_before_extension() {
+        let parser = ParserAdapter::new();
+
+        assert_eq!(detect_file_type("data.txt", Some("text/csv; charset=utf-8"), b"a,b\n1,2\n", &parser), "csv");
+        assert_eq!(detect_file_type("data.csv", Some("application/octet-stream"), b"a,b\n1,2\n", &parser), "csv");
+        assert_eq!(detect_file_type("feed", Some("application/x-ndjson"), b"{\"a\":1}\n", &parser), "ndjson");
+    }
+
+    #[test]
+    fn test_json_extension_with_ndjson_content() {
+        let parser = ParserAdapter::new();
+
+        assert_eq!(detect_file_type("events.json", None, b"{\"a\":1}\n{\"a\":2}\n", &parser), "ndjson");
+        assert_eq!(detect_file_type("events.json", None, b"{\n  \"a\": 1\n}\n", &parser), "json");
+    }
+
+    #[test]
+    fn test_probe_for_extensionless_keys() {
+        let parser = ParserAdapter::new();
+
+        assert_eq!(detect_file_type("inbox/1234", None, b"\xef\xbb\xbf[{\"a\": 1}]", &parser), "json");
+        assert_eq!(detect_file_type("inbox/1234", None, b"{\"a\":1}\n{\"a\":2}", &parser), "ndjson");
+        assert_eq!(detect_file_type("inbox/1234", None, b"<?xml version=\"1.0\"?><r/>", &parser), "xml");
+        assert_eq!(detect_file_type("inbox/1234", None, b"name;age\nJohn;25\nJane;3", &parser), "csv");
+        assert_eq!(detect_file_type("inbox/1234", None, b"just some notes\nmore, notes here\n", &parser), "txt");
+    }
+
+    #[test]
+    fn test_unknown_binary_falls_back_to_extension() {
+        let parser = ParserAdapter::new();
+
+        assert_eq!(detect_file_type("blob.dat", None, b"\x00\x01\x02", &parser), "dat");
+        assert_eq!(detect_file_type("blob", None, b"\x00\x01\x02", &parser), "");
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use crate::infrastructure::parsers::fixed_width_parser::{parse_fixed_width_with_config, stream_fixed_width_with_config};
+
+    const EXTRACT: &str = "\
+ACCT      NAME                BALANCE   OPENED
+0000012345John Smith          0001250.5020230115
+0000067890Zoë Müller          -000042.0020221231
+0000099999                              20240229
+";
+
+    fn layout() -> serde_json::Value {
+        json!({
+            "skip_rows": 1,
+            "fields": [
+                {"name": "account", "start": 1, "length": 10},
+                {"name": "name", "start": 11, "end": 30, "trim": "right"},
+                {"name": "balance", "start": 31, "length": 10, "type": "decimal", "scale": 2},
+                {"name": "opened", "start": 41, "length": 8, "type": "date", "format": "%Y%m%d"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_fields_by_start_length_and_end() {
+        let result = parse_fixed_width_with_config(EXTRACT.as_bytes(), Some(&layout())).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0], json!({"account": "0000012345", "name": "John Smith", "balance": 1250.5, "opened": "2023-01-15", "_source_row": 2}));
+        assert_eq!(result[1]["name"], "Zoë Müller");
+        assert_eq!(result[1]["balance"], -42);
+    }
+
+    #[test]
+    fn test_blank_typed_field_is_null() {
+        let result = parse_fixed_width_with_config(EXTRACT.as_bytes(), Some(&layout())).unwrap();
+
+        assert_eq!(result[2]["name"], "");
+        assert_eq!(result[2]["balance"], json!(null));
+        assert_eq!(result[2]["opened"], "2024-02-29");
+    }
+
+    #[test]
+    fn test_trim_modes() {
+        let config = json!({"fields": [
+            {"name": "left", "start": 1, "length": 5, "trim": "left"},
+            {"name": "none", "start": 6, "length": 5, "trim": false}
+        ]});
+
+        let result = parse_fixed_width_with_config(b"  ab   cd ", Some(&config)).unwrap();
+
+        assert_eq!(result[0], json!({"left": "ab ", "none": "  cd ", "_source_row": 1}));
+    }
+
+    #[test]
+    fn test_short_lines_yield_empty_fields() {
+        let config = json!({"fields": [
+            {"name": "code", "start": 1, "length": 3},
+            {"name": "note", "start": 4, "length": 10}
+        ]});
+
+        let result = parse_fixed_width_with_config(b"ABC\nDEFhello", Some(&config)).unwrap();
+
+        assert_eq!(result[0]["note"], "");
+        assert_eq!(result[1]["note"], "hello");
+    }
+
+    #[test]
+    fn test_conversion_error_reports_line_and_field() {
+        let config = json!({"fields": [{"name": "qty", "start": 1, "length": 3, "type": "integer"}]});
+
+        let err = parse_fixed_width_with_config(b"001\nx02", Some(&config)).unwrap_err();
+
+        assert!(err.to_string().contains("Fixed-width line 2, field 'qty'"));
+    }
+
+    #[test]
+    fn test_invalid_layouts_are_config_errors() {
+        for config in [
+            json!({}),
+            json!({"fields": [{"name": "a", "start": 0, "length": 2}]}),
+            json!({"fields": [{"name": "a", "start": 3, "end": 2}]}),
+            json!({"fields": [{"name": "a", "start": 1, "length": 2, "end": 2}]}),
+            json!({"fields": [{"name": "a", "start": 1, "length": 2, "trim": "middle"}]}),
+        ] {
+            assert!(parse_fixed_width_with_config(b"abc", Some(&config)).is_err(), "{}", config);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_yields_batches() {
+        let stream = Box::pin(std::io::Cursor::new(EXTRACT.as_bytes().to_vec()));
+
+        let batches: Vec<_> = stream_fixed_width_with_config(stream, Some(layout()), 2).collect().await;
+
+        let sizes: Vec<usize> = batches.into_iter().map(|b| b.unwrap().len()).collect();
+        assert_eq!(sizes, vec![2, 1]);
+    }
+}
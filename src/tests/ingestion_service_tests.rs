@@ -0,0 +1,642 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex};
+    use async_compression::tokio::bufread::GzipEncoder;
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+    use tokio::io::AsyncReadExt;
+    use zip::write::SimpleFileOptions;
+    use crate::application::ingestion_service::IngestionService;
+    use crate::domain::{
+        error::IngestionError,
+        models::{DocumentCounts, DuplicatePolicy, FileMetadata, FileToProcess, IngestionConfigRule, IngestionLog, IngestionStatus, SourceVersion},
+        ports::{ConfigRepository, DataRepository, FileFetcher, LogRepository},
+        rule_matching::{FanoutMode, RuleMatcher},
+    };
+    use crate::infrastructure::{decompression_adapter::DecompressionAdapter, parser_adapter::ParserAdapter};
+
+    struct StaticFetcher {
+        content: Vec<u8>,
+        tag_requests: AtomicUsize,
+    }
+
+    impl StaticFetcher {
+        fn new(content: &[u8]) -> Self {
+            Self { content: content.to_vec(), tag_requests: AtomicUsize::new(0) }
+        }
+    }
+
+    #[async_trait]
+    impl FileFetcher for StaticFetcher {
+        async fn fetch_file(&self, _bucket: &str, _key: &str) -> Result<Vec<u8>, IngestionError> {
+            Ok(self.content.clone())
+        }
+
+        async fn fetch_metadata(&self, _bucket: &str, _key: &str) -> Result<FileMetadata, IngestionError> {
+            Ok(FileMetadata { etag: Some("etag-1".to_string()), ..Default::default() })
+        }
+
+        async fn fetch_tags(&self, _bucket: &str, _key: &str) -> Result<HashMap<String, String>, IngestionError> {
+            self.tag_requests.fetch_add(1, Ordering::SeqCst);
+            Ok(HashMap::from([("classification".to_string(), "pii".to_string())]))
+        }
+    }
+
+    struct StaticConfig {
+        rules: Vec<IngestionConfigRule>,
+    }
+
+    #[async_trait]
+    impl ConfigRepository for StaticConfig {
+        async fn load_rules(&self) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+            Ok(self.rules.clone())
+        }
+
+        async fn get_config_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Option<IngestionConfigRule>, IngestionError> {
+            Ok(self.get_configs_for_file(file, metadata).await?.into_iter().next())
+        }
+
+        async fn get_configs_for_file(&self, file: &FileToProcess, metadata: &FileMetadata) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+            let matcher = RuleMatcher::new(self.rules.clone())?;
+            Ok(matcher.select_targets(file, metadata, FanoutMode::Flagged).into_iter().cloned().collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingDataRepo {
+        batches: Mutex<Vec<(String, Vec<serde_json::Value>)>>,
+        deleted: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl DataRepository for RecordingDataRepo {
+        async fn insert_documents(&self, target_table: &str, documents: &[serde_json::Value], _log_id: &str) -> Result<Vec<String>, IngestionError> {
+            self.batches.lock().unwrap().push((target_table.to_string(), documents.to_vec()));
+            Ok(documents.iter().enumerate().map(|(i, _)| i.to_string()).collect())
+        }
+
+        async fn delete_documents(&self, target_table: &str, log_id: &str) -> Result<u64, IngestionError> {
+            self.deleted.lock().unwrap().push((target_table.to_string(), log_id.to_string()));
+            Ok(0)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingLogRepo {
+        logs: Mutex<Vec<String>>,
+        targets: Mutex<Vec<String>>,
+        rules: Mutex<Vec<(Option<String>, u32)>>,
+        updates: Mutex<Vec<(IngestionStatus, Option<String>)>>,
+        counts: Mutex<Vec<DocumentCounts>>,
+        stored: Mutex<Vec<IngestionLog>>,
+    }
+
+    #[async_trait]
+    impl LogRepository for RecordingLogRepo {
+        async fn insert_log(&self, log: &IngestionLog) -> Result<String, IngestionError> {
+            let mut logs = self.logs.lock().unwrap();
+            logs.push(log.file_name.clone());
+            self.targets.lock().unwrap().push(log.target_table.clone());
+            self.rules.lock().unwrap().push((log.rule_id.clone(), log.rule_version));
+            self.stored.lock().unwrap().push(log.clone());
+            Ok(format!("log-{}", logs.len()))
+        }
+
+        async fn update_log(&self, log_id: &str, end_time: DateTime<Utc>, status: IngestionStatus, message: Option<String>, counts: DocumentCounts) -> Result<(), IngestionError> {
+            let index: usize = log_id.trim_start_matches("log-").parse().unwrap();
+            let mut stored = self.stored.lock().unwrap();
+            stored[index - 1].end_time = Some(end_time);
+            stored[index - 1].status = status.clone();
+            self.updates.lock().unwrap().push((status, message));
+            self.counts.lock().unwrap().push(counts);
+            Ok(())
+        }
+
+        async fn find_logs(&self, source: &SourceVersion, target_table: &str) -> Result<Vec<(String, IngestionLog)>, IngestionError> {
+            Ok(self.stored.lock().unwrap().iter().enumerate()
+                .filter(|(_, log)| log.source.as_ref() == Some(source) && log.target_table == target_table)
+                .map(|(i, log)| (format!("log-{}", i + 1), log.clone()))
+                .collect())
+        }
+    }
+
+    fn build_service(content: &[u8], rule: IngestionConfigRule) -> (IngestionService, Arc<RecordingDataRepo>, Arc<RecordingLogRepo>) {
+        build_service_with_rules(content, vec![rule])
+    }
+
+    fn build_service_with_rules(content: &[u8], rules: Vec<IngestionConfigRule>) -> (IngestionService, Arc<RecordingDataRepo>, Arc<RecordingLogRepo>) {
+        let data_repo = Arc::new(RecordingDataRepo::default());
+        let log_repo = Arc::new(RecordingLogRepo::default());
+        let service = IngestionService::new(
+            Arc::new(StaticFetcher::new(content)),
+            Arc::new(DecompressionAdapter::new()),
+            Arc::new(ParserAdapter::new()),
+            Arc::new(StaticConfig { rules }),
+            data_repo.clone(),
+            log_repo.clone(),
+        );
+        (service, data_repo, log_repo)
+    }
+
+    fn file(key: &str) -> FileToProcess {
+        FileToProcess {
+            bucket: "bucket".to_string(),
+            key: key.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_csv_is_stored_in_bounded_batches() {
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"batch_size": 2})),
+            ..IngestionConfigRule::new(".*\\.csv$", "csv_data")
+        };
+        let (service, data_repo, log_repo) = build_service(b"name\na\nb\nc\nd\ne", rule);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        let sizes: Vec<usize> = batches.iter().map(|(_, docs)| docs.len()).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert_eq!(batches[0].1[0]["file_name"], "bucket/data/people.csv");
+        assert!(matches!(log_repo.updates.lock().unwrap()[0].0, IngestionStatus::Success));
+    }
+
+    #[tokio::test]
+    async fn test_stream_parse_error_marks_log_failed() {
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"batch_size": 1})),
+            ..IngestionConfigRule::new(".*\\.csv$", "csv_data")
+        };
+        let (service, data_repo, log_repo) = build_service(b"name,age\nJohn,25\nJane", rule);
+
+        let result = service.process_file(file("data/people.csv")).await;
+
+        assert!(result.is_err());
+        assert_eq!(data_repo.batches.lock().unwrap().len(), 1);
+        assert!(matches!(log_repo.updates.lock().unwrap()[0].0, IngestionStatus::Failed));
+    }
+
+    #[tokio::test]
+    async fn test_non_streaming_type_is_stored_in_one_call() {
+        let rule = IngestionConfigRule::new(".*\\.json$", "json_data");
+        let (service, data_repo, _log_repo) = build_service(br#"[{"a": 1}, {"a": 2}]"#, rule);
+
+        service.process_file(file("data/items.json")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, "json_data");
+        assert_eq!(batches[0].1.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_gzip_file_is_parsed_by_inner_extension() {
+        let rule = IngestionConfigRule::new(".*\\.csv\\.gz$", "csv_data");
+        let mut gz = Vec::new();
+        GzipEncoder::new(&b"name\na\nb"[..]).read_to_end(&mut gz).await.unwrap();
+        let (service, data_repo, log_repo) = build_service(&gz, rule);
+
+        service.process_file(file("data/people.csv.gz")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches[0].1.len(), 2);
+        assert_eq!(batches[0].1[0]["file_name"], "bucket/data/people.csv.gz");
+        assert_eq!(*log_repo.logs.lock().unwrap(), vec!["bucket/data/people.csv.gz"]);
+    }
+
+    #[tokio::test]
+    async fn test_zip_entries_get_their_own_log() {
+        let rule = IngestionConfigRule::new(".*", "mixed");
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.start_file("people.csv", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"name\na").unwrap();
+        writer.start_file("items.json", SimpleFileOptions::default()).unwrap();
+        writer.write_all(br#"[{"a": 1}, {"a": 2}]"#).unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+        let (service, data_repo, log_repo) = build_service(&archive, rule);
+
+        service.process_file(file("drop/batch.zip")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].1[0]["file_name"], "bucket/drop/batch.zip/people.csv");
+        assert_eq!(batches[1].1.len(), 2);
+        assert_eq!(*log_repo.logs.lock().unwrap(), vec!["bucket/drop/batch.zip/people.csv", "bucket/drop/batch.zip/items.json"]);
+        assert_eq!(log_repo.updates.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_zip_entry_does_not_stop_other_entries() {
+        let rule = IngestionConfigRule::new(".*", "mixed");
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.start_file("broken.json", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"{not json").unwrap();
+        writer.start_file("items.json", SimpleFileOptions::default()).unwrap();
+        writer.write_all(br#"[{"a": 1}]"#).unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+        let (service, data_repo, log_repo) = build_service(&archive, rule);
+
+        let result = service.process_file(file("batch.zip")).await;
+
+        assert!(matches!(result, Err(IngestionError::Archive(_))));
+        assert_eq!(data_repo.batches.lock().unwrap().len(), 1);
+        assert_eq!(log_repo.logs.lock().unwrap().len(), 2);
+        let updates = log_repo.updates.lock().unwrap();
+        assert!(matches!(updates[0].0, IngestionStatus::Failed));
+        assert!(matches!(updates[1].0, IngestionStatus::Success));
+    }
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_zip_entries_are_logged_as_skipped() {
+        let archive = zip_of(&[("people.csv", b"name\na"), ("notes.txt", b"hello")]);
+        let (service, data_repo, log_repo) = build_service(&archive, IngestionConfigRule::new(".*\\.csv$", "people"));
+
+        service.process_file(file("batch.zip")).await.unwrap();
+
+        assert_eq!(data_repo.batches.lock().unwrap().len(), 1);
+        assert_eq!(*log_repo.logs.lock().unwrap(), vec!["bucket/batch.zip/people.csv", "bucket/batch.zip/notes.txt"]);
+        let skipped = &log_repo.stored.lock().unwrap()[1];
+        assert_eq!(skipped.status, IngestionStatus::Skipped);
+        assert!(skipped.end_time.is_some());
+        assert_eq!(skipped.source.as_ref().unwrap().key, "batch.zip/notes.txt");
+    }
+
+    #[tokio::test]
+    async fn test_archive_with_nothing_to_ingest_is_logged() {
+        let archive = zip_of(&[("notes.txt", b"hello"), ("readme.md", b"# hi")]);
+        let (service, data_repo, log_repo) = build_service(&archive, IngestionConfigRule::new(".*\\.csv$", "people"));
+
+        service.process_file(file("batch.zip")).await.unwrap();
+
+        assert!(data_repo.batches.lock().unwrap().is_empty());
+        assert_eq!(log_repo.logs.lock().unwrap().last().unwrap(), "bucket/batch.zip");
+        let stored = log_repo.stored.lock().unwrap();
+        assert!(stored.iter().all(|log| log.status == IngestionStatus::Skipped));
+        assert_eq!(stored[2].message.as_deref(), Some("None of the 2 entries of the archive matches a configuration rule"));
+    }
+
+    #[tokio::test]
+    async fn test_extensionless_key_is_sniffed() {
+        let rule = IngestionConfigRule::new("^inbox/", "events");
+        let (service, data_repo, _log_repo) = build_service(b"{\"a\": 1}\n{\"a\": 2}\n{\"a\": 3}\n", rule);
+
+        service.process_file(file("inbox/8f3a2c")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches[0].1.len(), 3);
+        assert_eq!(batches[0].1[2]["a"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_rule_file_type_overrides_detection() {
+        let rule = IngestionConfigRule {
+            file_type: Some("CSV".to_string()),
+            ..IngestionConfigRule::new(".*\\.json$", "csv_data")
+        };
+        let (service, data_repo, _log_repo) = build_service(b"name,age\nJohn,25", rule);
+
+        service.process_file(file("data/mislabeled.json")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches[0].1[0]["name"], "John");
+    }
+
+    #[tokio::test]
+    async fn test_fanout_stores_each_target_with_its_own_config() {
+        let rules = vec![
+            IngestionConfigRule::new(".*\\.csv$", "people"),
+            IngestionConfigRule { parser_config: Some(json!({"has_headers": false})), fanout: true, ..IngestionConfigRule::new("^data/", "people_raw") },
+        ];
+        let (service, data_repo, log_repo) = build_service_with_rules(b"name\nJohn", rules);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].0, "people");
+        assert_eq!(batches[0].1.len(), 1);
+        assert_eq!(batches[1].0, "people_raw");
+        assert_eq!(batches[1].1.len(), 2);
+        assert_eq!(*log_repo.targets.lock().unwrap(), vec!["people", "people_raw"]);
+        assert_eq!(*log_repo.logs.lock().unwrap(), vec!["bucket/data/people.csv", "bucket/data/people.csv"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_fanout_target_does_not_hide_the_others() {
+        let rules = vec![
+            IngestionConfigRule { file_type: Some("json".to_string()), ..IngestionConfigRule::new(".*\\.csv$", "people_json") },
+            IngestionConfigRule { fanout: true, ..IngestionConfigRule::new("^data/", "people") },
+        ];
+        let (service, data_repo, log_repo) = build_service_with_rules(b"name\nJohn", rules);
+
+        let result = service.process_file(file("data/people.csv")).await;
+
+        match result {
+            Err(IngestionError::Fanout(message)) => assert!(message.starts_with("1 of 2 targets"), "{}", message),
+            other => panic!("expected a fan-out error, got {:?}", other),
+        }
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, "people");
+        assert_eq!(*log_repo.targets.lock().unwrap(), vec!["people_json", "people"]);
+        let updates = log_repo.updates.lock().unwrap();
+        assert!(matches!(updates[0].0, IngestionStatus::Failed));
+        assert!(matches!(updates[1].0, IngestionStatus::Success));
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_fetched_only_when_a_rule_uses_them() {
+        let mut tagged = IngestionConfigRule { priority: 10, ..IngestionConfigRule::new(".*\\.csv$", "people_pii") };
+        tagged.matchers.tags.insert("classification".to_string(), "pii".to_string());
+
+        for (rules, expected_table, expected_requests) in [
+            (vec![IngestionConfigRule::new(".*\\.csv$", "people")], "people", 0),
+            (vec![IngestionConfigRule::new(".*\\.csv$", "people"), tagged], "people_pii", 1),
+        ] {
+            let fetcher = Arc::new(StaticFetcher::new(b"name\nJohn"));
+            let data_repo = Arc::new(RecordingDataRepo::default());
+            let service = IngestionService::new(
+                fetcher.clone(),
+                Arc::new(DecompressionAdapter::new()),
+                Arc::new(ParserAdapter::new()),
+                Arc::new(StaticConfig { rules }),
+                data_repo.clone(),
+                Arc::new(RecordingLogRepo::default()),
+            );
+
+            service.process_file(file("data/people.csv")).await.unwrap();
+
+            assert_eq!(data_repo.batches.lock().unwrap()[0].0, expected_table);
+            assert_eq!(fetcher.tag_requests.load(Ordering::SeqCst), expected_requests);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_records_rule_id_and_version() {
+        let mut current = IngestionConfigRule::new(".*\\.csv$", "people");
+        current.rule_id = Some("people".to_string());
+        current.version = 2;
+        let mut previous = current.clone();
+        previous.version = 1;
+        previous.target_table = "people_old".to_string();
+        let (service, data_repo, log_repo) = build_service_with_rules(b"name\nJohn", vec![previous, current]);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        assert_eq!(data_repo.batches.lock().unwrap()[0].0, "people");
+        assert_eq!(*log_repo.rules.lock().unwrap(), vec![(Some("people".to_string()), 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_transform_runs_before_documents_are_stored() {
+        let mut rule = IngestionConfigRule::new(".*\\.csv$", "people");
+        rule.transform = Some(json!([
+            {"rename": {"name": "address.name"}},
+            {"template": {"greeting": "Hello {address.name}"}},
+            {"nest": {}}
+        ]));
+        let (service, data_repo, _log_repo) = build_service(b"name\nJohn\nJane", rule);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches[0].1[1]["address"], json!({"name": "Jane"}));
+        assert_eq!(batches[0].1[1]["greeting"], "Hello Jane");
+        assert_eq!(batches[0].1[1]["file_name"], "bucket/data/people.csv");
+    }
+
+    #[tokio::test]
+    async fn test_documents_failing_the_transform_go_to_the_rejects_table() {
+        let mut rule = IngestionConfigRule::new(".*\\.csv$", "people");
+        rule.transform = Some(json!([{"parse_date": {"field": "born", "format": "%Y-%m-%d"}}]));
+        let (service, data_repo, log_repo) = build_service(b"born\n2000-01-01\nyesterday\n2001-02-03", rule);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches[0].0, "people");
+        assert_eq!(batches[0].1.len(), 2);
+        assert!(batches[0].1.iter().all(|doc| doc.get("_source_row").is_none()));
+        assert_eq!(batches[1].0, "people_rejects");
+        let reject = &batches[1].1[0];
+        assert_eq!(reject["source_row"], 3);
+        assert_eq!(reject["document"]["born"], "yesterday");
+        assert!(reject["errors"][0].as_str().unwrap().contains("born"), "{}", reject);
+        assert_eq!(*log_repo.counts.lock().unwrap(), vec![DocumentCounts { accepted: 2, rejected: 1 }]);
+        assert!(matches!(log_repo.updates.lock().unwrap()[0].0, IngestionStatus::Success));
+    }
+
+    fn with_schema(max_reject_ratio: Option<f64>) -> IngestionConfigRule {
+        let mut rule = IngestionConfigRule::new(".*\\.csv$", "people");
+        rule.schema = Some(json!({
+            "type": "object",
+            "properties": {"age": {"type": "string", "pattern": "^[0-9]+$"}},
+            "required": ["age"]
+        }));
+        rule.max_reject_ratio = max_reject_ratio;
+        rule
+    }
+
+    #[tokio::test]
+    async fn test_invalid_documents_go_to_the_rejects_table() {
+        let (service, data_repo, log_repo) = build_service(b"name,age\nJohn,30\nJane,-1\nJim,41", with_schema(None));
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].0, "people");
+        assert_eq!(batches[0].1.len(), 2);
+        assert_eq!(batches[1].0, "people_rejects");
+        let reject = &batches[1].1[0];
+        assert_eq!(reject["source_row"], 3);
+        assert_eq!(reject["document_index"], 2);
+        assert_eq!(reject["document"]["name"], "Jane");
+        assert_eq!(reject["file_name"], "bucket/data/people.csv");
+        assert!(reject["errors"][0].as_str().unwrap().starts_with("/age: "), "{}", reject);
+        assert_eq!(*log_repo.counts.lock().unwrap(), vec![DocumentCounts { accepted: 2, rejected: 1 }]);
+        assert!(matches!(log_repo.updates.lock().unwrap()[0].0, IngestionStatus::Success));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_rejects_keep_their_document_index() {
+        let mut rule = with_schema(None);
+        rule.parser_config = Some(json!({"batch_size": 2}));
+        let (service, data_repo, log_repo) = build_service(b"age\n1\n2\nx\n4\n5", rule);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        let rejects: Vec<_> = batches.iter().filter(|(table, _)| table == "people_rejects").flat_map(|(_, docs)| docs).collect();
+        assert_eq!(rejects.len(), 1);
+        assert_eq!(rejects[0]["document_index"], 3);
+        assert_eq!(*log_repo.counts.lock().unwrap(), vec![DocumentCounts { accepted: 4, rejected: 1 }]);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_carry_the_source_row_past_skipped_and_multi_line_rows() {
+        let mut rule = with_schema(None);
+        rule.parser_config = Some(json!({"skip_rows": 1}));
+        let (service, data_repo, _log_repo) = build_service(b"exported today\nnote,age\n\"two\nlines\",1\nplain,x", rule);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let batches = data_repo.batches.lock().unwrap();
+        let rejects: Vec<_> = batches.iter().filter(|(table, _)| table == "people_rejects").flat_map(|(_, docs)| docs).collect();
+        assert_eq!(rejects.len(), 1);
+        assert_eq!(rejects[0]["source_row"], 5);
+        assert_eq!(rejects[0]["document_index"], 2);
+        assert!(rejects[0]["document"].get("_source_row").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reject_ratio_above_threshold_fails_the_log() {
+        let (service, _data_repo, log_repo) = build_service(b"name,age\nJohn,30\nJane,-1\nJim,old", with_schema(Some(0.5)));
+
+        let result = service.process_file(file("data/people.csv")).await;
+
+        match result {
+            Err(IngestionError::Rejects(message)) => assert!(message.starts_with("2 of 3 documents rejected"), "{}", message),
+            other => panic!("expected a rejects error, got {:?}", other),
+        }
+        let updates = log_repo.updates.lock().unwrap();
+        assert!(matches!(updates[0].0, IngestionStatus::Failed));
+        assert_eq!(*log_repo.counts.lock().unwrap(), vec![DocumentCounts { accepted: 1, rejected: 2 }]);
+    }
+
+    async fn process_twice(on_duplicate: DuplicatePolicy) -> (Arc<RecordingDataRepo>, Arc<RecordingLogRepo>) {
+        let mut rule = IngestionConfigRule::new(".*\\.csv$", "people");
+        rule.on_duplicate = on_duplicate;
+        let (service, data_repo, log_repo) = build_service(b"name\nJohn", rule);
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+        service.process_file(file("data/people.csv")).await.unwrap();
+        (data_repo, log_repo)
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_delivery_is_skipped_by_default() {
+        let (data_repo, log_repo) = process_twice(DuplicatePolicy::default()).await;
+
+        assert_eq!(data_repo.batches.lock().unwrap().len(), 1);
+        assert_eq!(log_repo.logs.lock().unwrap().len(), 1);
+        let source = log_repo.stored.lock().unwrap()[0].source.clone().unwrap();
+        assert_eq!(source, SourceVersion {
+            bucket: "bucket".to_string(),
+            key: "data/people.csv".to_string(),
+            version_id: None,
+            etag: Some("etag-1".to_string()),
+        });
+    }
+
+    #[tokio::test]
+    async fn test_failed_ingestion_is_not_skipped() {
+        let rule = IngestionConfigRule { file_type: Some("json".to_string()), ..IngestionConfigRule::new(".*\\.csv$", "people") };
+        let (service, _data_repo, log_repo) = build_service(b"name\nJohn", rule);
+
+        assert!(service.process_file(file("data/people.csv")).await.is_err());
+        assert!(service.process_file(file("data/people.csv")).await.is_err());
+
+        assert_eq!(log_repo.logs.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_deletes_documents_of_the_failed_ingestion() {
+        let rule = IngestionConfigRule { max_reject_ratio: Some(0.0), schema: Some(json!({"required": ["email"]})), ..IngestionConfigRule::new(".*\\.csv$", "people") };
+        let (service, data_repo, log_repo) = build_service(b"name\nJohn", rule);
+
+        assert!(service.process_file(file("data/people.csv")).await.is_err());
+        assert!(service.process_file(file("data/people.csv")).await.is_err());
+
+        assert_eq!(*data_repo.deleted.lock().unwrap(), vec![
+            ("people".to_string(), "log-1".to_string()),
+            ("people_rejects".to_string(), "log-1".to_string()),
+        ]);
+        assert_eq!(log_repo.logs.lock().unwrap().len(), 2);
+    }
+
+    fn open_log(started: DateTime<Utc>) -> IngestionLog {
+        IngestionLog {
+            file_name: "people.csv".to_string(),
+            target_table: "people".to_string(),
+            rule_id: None,
+            rule_version: 1,
+            accepted_count: 0,
+            rejected_count: 0,
+            source: Some(SourceVersion {
+                bucket: "bucket".to_string(),
+                key: "data/people.csv".to_string(),
+                version_id: None,
+                etag: Some("etag-1".to_string()),
+            }),
+            start_time: started,
+            end_time: None,
+            status: IngestionStatus::InProgress,
+            message: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_during_an_ingestion_in_progress_is_skipped() {
+        let (service, data_repo, log_repo) = build_service(b"name\nJohn", IngestionConfigRule::new(".*\\.csv$", "people"));
+        log_repo.insert_log(&open_log(Utc::now())).await.unwrap();
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        assert!(data_repo.batches.lock().unwrap().is_empty());
+        assert_eq!(log_repo.logs.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_log_in_progress_is_failed_and_retried() {
+        let (service, data_repo, log_repo) = build_service(b"name\nJohn", IngestionConfigRule::new(".*\\.csv$", "people"));
+        let service = service.with_stale_log_timeout(std::time::Duration::from_secs(600));
+        log_repo.insert_log(&open_log(Utc::now() - chrono::Duration::minutes(11))).await.unwrap();
+
+        service.process_file(file("data/people.csv")).await.unwrap();
+
+        let stored = log_repo.stored.lock().unwrap();
+        assert_eq!(stored[0].status, IngestionStatus::Failed);
+        assert!(stored[0].end_time.is_some());
+        assert_eq!(stored[1].status, IngestionStatus::Success);
+        assert_eq!(data_repo.batches.lock().unwrap().len(), 1);
+        assert_eq!(*data_repo.deleted.lock().unwrap(), vec![
+            ("people".to_string(), "log-1".to_string()),
+            ("people_rejects".to_string(), "log-1".to_string()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_replace_deletes_documents_of_the_earlier_ingestion() {
+        let (data_repo, log_repo) = process_twice(DuplicatePolicy::Replace).await;
+
+        assert_eq!(data_repo.batches.lock().unwrap().len(), 2);
+        assert_eq!(*data_repo.deleted.lock().unwrap(), vec![
+            ("people".to_string(), "log-1".to_string()),
+            ("people_rejects".to_string(), "log-1".to_string()),
+        ]);
+        assert_eq!(log_repo.logs.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_append_ingests_again_without_deleting() {
+        let (data_repo, log_repo) = process_twice(DuplicatePolicy::Append).await;
+
+        assert_eq!(data_repo.batches.lock().unwrap().len(), 2);
+        assert!(data_repo.deleted.lock().unwrap().is_empty());
+        assert_eq!(log_repo.logs.lock().unwrap().len(), 2);
+    }
+}
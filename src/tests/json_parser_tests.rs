@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use crate::infrastructure::parsers::json_parser::{parse_json, parse_json_with_config};
+    use serde_json::json;
+
+    const API_DUMP: &[u8] = br#"{
+        "meta": {"source": "crm", "exported_at": "2024-05-01"},
+        "data": {"items": [{"id": 1}, {"id": 2}]}
+    }"#;
+
+    #[test]
+    fn test_json_top_level_array_and_object() {
+        assert_eq!(parse_json(br#"[{"a": 1}, {"a": 2}]"#).unwrap().len(), 2);
+        assert_eq!(parse_json(br#"{"a": 1}"#).unwrap(), vec![json!({"a": 1})]);
+    }
+
+    #[test]
+    fn test_json_records_path_pointer_and_dotted() {
+        let config = json!({"records_path": "/data/items"});
+        let result = parse_json_with_config(API_DUMP, Some(&config)).unwrap();
+        assert_eq!(result, vec![json!({"id": 1}), json!({"id": 2})]);
+
+        let config = json!({"records_path": "data.items"});
+        let result = parse_json_with_config(API_DUMP, Some(&config)).unwrap();
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn test_json_metadata_fields_copied_into_records() {
+        let config = json!({
+            "records_path": "data.items",
+            "metadata_fields": ["meta.source", "/meta/exported_at"]
+        });
+        let result = parse_json_with_config(API_DUMP, Some(&config)).unwrap();
+        assert_eq!(result[1], json!({"id": 2, "source": "crm", "exported_at": "2024-05-01"}));
+
+        let config = json!({
+            "records_path": "data.items",
+            "metadata_fields": {"origin": "meta.source"}
+        });
+        let result = parse_json_with_config(API_DUMP, Some(&config)).unwrap();
+        assert_eq!(result[0], json!({"id": 1, "origin": "crm"}));
+    }
+
+    #[test]
+    fn test_json_metadata_fields_do_not_overwrite_record_fields() {
+        let config = json!({
+            "records_path": "data.items",
+            "metadata_fields": {"id": "meta.source", "origin": "meta.source"}
+        });
+        let result = parse_json_with_config(API_DUMP, Some(&config)).unwrap();
+
+        assert_eq!(result[0], json!({"id": 1, "origin": "crm"}));
+    }
+
+    #[test]
+    fn test_json_missing_records_path() {
+        let config = json!({"records_path": "data.rows"});
+        let err = parse_json_with_config(API_DUMP, Some(&config)).unwrap_err();
+
+        assert!(err.to_string().contains("data.rows"));
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::infrastructure::parsers::log_parser::parse_log_with_config;
+
+    const LOG: &str = "\
+2024-03-01 12:00:01 INFO [main] Service started
+2024-03-01 12:00:05 ERROR [worker-1] Request failed status=500
+java.lang.IllegalStateException: boom
+    at com.example.Worker.run(Worker.java:42)
+    at java.lang.Thread.run(Thread.java:750)
+2024-03-01 12:00:06 WARN [worker-2] Slow response status=200
+";
+
+    const PATTERN: &str = r"^(?P<timestamp>\S+ \S+) (?P<level>[A-Z]+) \[(?P<thread>[^\]]+)\] (?P<message>.*?)(?: status=(?P<status>\d+))?$";
+
+    #[test]
+    fn test_named_captures_become_fields() {
+        let config = json!({"line_pattern": PATTERN, "columns": {"status": "integer"}});
+
+        let result = parse_log_with_config(LOG.as_bytes(), Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0], json!({
+            "line_number": 1,
+            "_source_row": 1,
+            "timestamp": "2024-03-01 12:00:01",
+            "level": "INFO",
+            "thread": "main",
+            "message": "Service started",
+            "status": null
+        }));
+        assert_eq!(result[2]["status"], 200);
+        assert_eq!(result[2]["line_number"], 6);
+    }
+
+    #[test]
+    fn test_unmatched_lines_attach_to_previous_entry() {
+        let config = json!({"line_pattern": PATTERN});
+
+        let result = parse_log_with_config(LOG.as_bytes(), Some(&config)).unwrap();
+
+        assert_eq!(result[1]["continuation"], "java.lang.IllegalStateException: boom\n    at com.example.Worker.run(Worker.java:42)\n    at java.lang.Thread.run(Thread.java:750)");
+        assert!(result[0].get("continuation").is_none());
+    }
+
+    #[test]
+    fn test_continuation_pattern_with_skip() {
+        let config = json!({
+            "line_pattern": PATTERN,
+            "continuation_pattern": r"^\s+at ",
+            "continuation_field": "stack_trace",
+            "on_unmatched": "skip"
+        });
+
+        let result = parse_log_with_config(LOG.as_bytes(), Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[1]["stack_trace"], "    at com.example.Worker.run(Worker.java:42)\n    at java.lang.Thread.run(Thread.java:750)");
+    }
+
+    #[test]
+    fn test_unmatched_line_fails_when_configured() {
+        let config = json!({"line_pattern": PATTERN, "continuation_pattern": r"^\s+at ", "on_unmatched": "fail"});
+
+        let err = parse_log_with_config(LOG.as_bytes(), Some(&config)).unwrap_err();
+
+        assert_eq!(err.to_string(), "Parsing error: Log line 3: does not match line_pattern");
+    }
+
+    #[test]
+    fn test_leading_unmatched_lines_are_skipped() {
+        let config = json!({"line_pattern": PATTERN});
+
+        let result = parse_log_with_config(format!("-- log rotated --\n{}", LOG).as_bytes(), Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0]["line_number"], 2);
+    }
+
+    #[test]
+    fn test_pattern_config_errors() {
+        assert!(parse_log_with_config(b"x", Some(&json!({"line_pattern": "(unclosed"}))).is_err());
+        assert!(parse_log_with_config(b"x", Some(&json!({"line_pattern": "^(\\w+)$"}))).is_err());
+        assert!(parse_log_with_config(b"x", Some(&json!({"line_pattern": PATTERN, "on_unmatched": "ignore"}))).is_err());
+    }
+}
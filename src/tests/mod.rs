@@ -1,2 +1,20 @@
 mod csv_parser_tests;
-mod config_matching_tests;
\ No newline at end of file
+mod config_matching_tests;
+mod ingestion_service_tests;
+mod excel_parser_tests;
+mod xml_parser_tests;
+mod ndjson_parser_tests;
+mod json_parser_tests;
+mod parquet_parser_tests;
+mod avro_parser_tests;
+mod decompression_adapter_tests;
+mod file_type_tests;
+mod pdf_parser_tests;
+mod fixed_width_parser_tests;
+mod log_parser_tests;
+mod encoding_tests;
+mod parser_registry_tests;
+mod rule_validator_tests;
+mod rule_cache_tests;
+mod transform_tests;
+mod document_schema_tests;mod couch_config_repo_tests;
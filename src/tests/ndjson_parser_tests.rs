@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::infrastructure::parsers::ndjson_parser::{parse_ndjson, parse_ndjson_with_config, stream_ndjson_with_config};
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    #[test]
+    fn test_ndjson_lines() {
+        let data = b"{\"id\": 1, \"name\": \"John\"}\n\n{\"id\": 2, \"name\": \"Jane\"}\r\n";
+        let result = parse_ndjson(data).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["name"], "John");
+        assert_eq!(result[1]["id"], 2);
+    }
+
+    #[test]
+    fn test_ndjson_fails_with_line_number() {
+        let data = b"{\"id\": 1}\n{\"id\": \n{\"id\": 3}";
+        let err = parse_ndjson(data).unwrap_err();
+
+        assert!(err.to_string().contains("NDJSON line 2"));
+    }
+
+    #[test]
+    fn test_ndjson_skips_bad_lines() {
+        let data = b"{\"id\": 1}\nnot json\n{\"id\": 3}";
+        let config = json!({"on_error": "skip"});
+        let result = parse_ndjson_with_config(data, Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[1]["id"], 3);
+    }
+
+    #[test]
+    fn test_ndjson_unknown_on_error_mode() {
+        let config = json!({"on_error": "ignore"});
+        assert!(parse_ndjson_with_config(b"{}", Some(&config)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_stream_batches() {
+        let data = b"{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}".to_vec();
+        let stream = Box::pin(std::io::Cursor::new(data));
+        let batches: Vec<_> = stream_ndjson_with_config(stream, None, 2).collect().await;
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1].as_ref().unwrap()[0]["id"], 3);
+    }
+}
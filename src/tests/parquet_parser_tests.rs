@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use futures_util::StreamExt;
+    use parquet::data_type::{ByteArray, ByteArrayType, Int32Type, Int64Type};
+    use parquet::file::{properties::WriterProperties, writer::SerializedFileWriter};
+    use parquet::schema::parser::parse_message_type;
+    use crate::infrastructure::parsers::parquet_parser::{parse_parquet, parse_parquet_with_config, stream_parquet_with_config};
+
+    const SCHEMA: &str = "
+        message order {
+            required int64 id;
+            optional binary name (UTF8);
+            required int64 created_at (TIMESTAMP_MILLIS);
+            required int32 amount (DECIMAL(9, 2));
+            optional group tags (LIST) {
+                repeated group list {
+                    optional binary element (UTF8);
+                }
+            }
+        }
+    ";
+
+    /// Writes one row group per entry of `groups`, each holding `(id, name)` rows.
+    fn build_parquet(groups: &[&[(i64, Option<&str>)]]) -> Vec<u8> {
+        let schema = Arc::new(parse_message_type(SCHEMA).unwrap());
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(Vec::new(), schema, props).unwrap();
+
+        for rows in groups {
+            let ids: Vec<i64> = rows.iter().map(|(id, _)| *id).collect();
+            let names: Vec<ByteArray> = rows.iter().filter_map(|(_, n)| n.map(ByteArray::from)).collect();
+            let name_levels: Vec<i16> = rows.iter().map(|(_, n)| if n.is_some() { 1 } else { 0 }).collect();
+            let timestamps: Vec<i64> = rows.iter().map(|(id, _)| 1_700_000_000_123 + id).collect();
+            let amounts: Vec<i32> = rows.iter().map(|(id, _)| -1050 + *id as i32).collect();
+
+            let mut row_group = writer.next_row_group().unwrap();
+            let mut column = row_group.next_column().unwrap().unwrap();
+            column.typed::<Int64Type>().write_batch(&ids, None, None).unwrap();
+            column.close().unwrap();
+
+            let mut column = row_group.next_column().unwrap().unwrap();
+            column.typed::<ByteArrayType>().write_batch(&names, Some(&name_levels), None).unwrap();
+            column.close().unwrap();
+
+            let mut column = row_group.next_column().unwrap().unwrap();
+            column.typed::<Int64Type>().write_batch(&timestamps, None, None).unwrap();
+            column.close().unwrap();
+
+            let mut column = row_group.next_column().unwrap().unwrap();
+            column.typed::<Int32Type>().write_batch(&amounts, None, None).unwrap();
+            column.close().unwrap();
+
+            // Every row gets the two-element list ["a", "b"]
+            let def_levels = vec![3i16; rows.len() * 2];
+            let rep_levels: Vec<i16> = (0..rows.len()).flat_map(|_| [0i16, 1]).collect();
+            let tags: Vec<ByteArray> = (0..rows.len()).flat_map(|_| [ByteArray::from("a"), ByteArray::from("b")]).collect();
+            let mut column = row_group.next_column().unwrap().unwrap();
+            column.typed::<ByteArrayType>().write_batch(&tags, Some(&def_levels), Some(&rep_levels)).unwrap();
+            column.close().unwrap();
+
+            row_group.close().unwrap();
+        }
+
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_parquet_type_mapping() {
+        let file = build_parquet(&[&[(1, Some("John")), (2, None)]]);
+        let result = parse_parquet(&file).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["id"], 1);
+        assert_eq!(result[0]["name"], "John");
+        assert_eq!(result[1]["name"], serde_json::Value::Null);
+        assert_eq!(result[0]["created_at"], "2023-11-14T22:13:20.124Z");
+        assert_eq!(result[0]["amount"], -10.49);
+        assert_eq!(result[0]["tags"], serde_json::json!(["a", "b"]));
+
+        let exact = parse_parquet_with_config(&file, Some(&serde_json::json!({"decimals_as_string": true}))).unwrap();
+        assert_eq!(exact[0]["amount"], "-10.49");
+    }
+
+    #[tokio::test]
+    async fn test_parquet_stream_spans_row_groups() {
+        let file = build_parquet(&[&[(1, Some("a")), (2, Some("b"))], &[(3, Some("c"))]]);
+        let stream = Box::pin(std::io::Cursor::new(file));
+        let batches: Vec<_> = stream_parquet_with_config(stream, None, 2).collect().await;
+
+        let ids: Vec<Vec<i64>> = batches.into_iter()
+            .map(|b| b.unwrap().iter().map(|d| d["id"].as_i64().unwrap()).collect())
+            .collect();
+        assert_eq!(ids, vec![vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_parquet_invalid_file() {
+        assert!(parse_parquet(b"not a parquet file").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use crate::domain::{error::IngestionError, ports::DataParser};
+    use crate::infrastructure::parser_adapter::ParserAdapter;
+    use crate::infrastructure::parsers::registry::{ConfigOption, FileParser, OptionType, ParserRegistry};
+
+    /// A parser a downstream crate might add: one document per `key=value` line.
+    struct KeyValueParser;
+
+    impl FileParser for KeyValueParser {
+        fn name(&self) -> &str {
+            "key_value"
+        }
+
+        fn file_types(&self) -> &[&str] {
+            &["properties", "txt"]
+        }
+
+        fn mime_types(&self) -> &[&str] {
+            &["text/x-java-properties"]
+        }
+
+        fn config_schema(&self) -> Vec<ConfigOption> {
+            vec![ConfigOption::new("separator", &[OptionType::String], "Key/value separator")]
+        }
+
+        fn accepts(&self, file_type: &str, config: Option<&Value>) -> bool {
+            file_type == "properties" || config.and_then(|c| c.get("separator")).is_some()
+        }
+
+        fn parse(&self, bytes: &[u8], config: Option<&Value>) -> Result<Vec<Value>, IngestionError> {
+            let separator = config.and_then(|c| c.get("separator")).and_then(|v| v.as_str()).unwrap_or("=");
+            Ok(String::from_utf8_lossy(bytes)
+                .lines()
+                .filter_map(|line| line.split_once(separator))
+                .map(|(k, v)| json!({"key": k.trim(), "value": v.trim()}))
+                .collect())
+        }
+    }
+
+    fn registry() -> ParserRegistry {
+        let mut registry = ParserRegistry::with_builtin_parsers();
+        registry.register(KeyValueParser);
+        registry
+    }
+
+    #[test]
+    fn test_builtin_parsers_are_registered() {
+        let registry = ParserRegistry::with_builtin_parsers();
+
+        let names: Vec<&str> = registry.parsers().iter().map(|p| p.name()).collect();
+        assert!(names.contains(&"csv"));
+        assert!(names.contains(&"pdf"));
+        assert_eq!(registry.resolve("jsonl", None).unwrap().name(), "ndjson");
+        assert_eq!(registry.file_type_for_mime("Text/CSV; charset=utf-8"), Some("csv".to_string()));
+        assert!(registry.resolve("docx", None).is_none());
+    }
+
+    #[test]
+    fn test_txt_variants_resolve_by_config() {
+        let registry = ParserRegistry::with_builtin_parsers();
+
+        assert_eq!(registry.resolve("txt", None).unwrap().name(), "txt");
+        assert_eq!(registry.resolve("txt", Some(&json!({"fields": []}))).unwrap().name(), "fixed_width");
+        assert_eq!(registry.resolve("log", Some(&json!({"line_pattern": "(?P<a>.*)"}))).unwrap().name(), "log");
+        assert_eq!(registry.resolve("fixed", None).unwrap().name(), "fixed_width");
+    }
+
+    #[tokio::test]
+    async fn test_custom_parser_is_used_by_adapter() {
+        let adapter = ParserAdapter::with_registry(registry());
+
+        let result = adapter.parse_with_config(b"host = db\nport = 5432", "properties", None).await.unwrap();
+
+        assert_eq!(result[1], json!({"key": "port", "value": "5432"}));
+        assert!(adapter.file_types().contains(&"properties".to_string()));
+        assert_eq!(adapter.file_type_for_content_type("text/x-java-properties"), Some("properties".to_string()));
+        assert!(!adapter.supports_streaming("properties", None));
+    }
+
+    #[tokio::test]
+    async fn test_later_registration_takes_precedence_when_it_accepts() {
+        let adapter = ParserAdapter::with_registry(registry());
+
+        let custom = adapter.parse_with_config(b"a: 1", "txt", Some(&json!({"separator": ":"}))).await.unwrap();
+        let builtin = adapter.parse_with_config(b"a: 1", "txt", None).await.unwrap();
+
+        assert_eq!(custom[0], json!({"key": "a", "value": "1"}));
+        assert_eq!(builtin[0]["content"], "a: 1");
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_type_is_unsupported() {
+        let adapter = ParserAdapter::with_registry(ParserRegistry::new());
+
+        let err = adapter.parse_with_config(b"a,b", "csv", None).await.unwrap_err();
+
+        assert_eq!(err.to_string(), "Parsing error: Unsupported file type: csv");
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use lopdf::{content::{Content, Operation}, dictionary, Document, Object, Stream};
+    use serde_json::json;
+    use crate::infrastructure::parsers::pdf_parser::{parse_pdf, parse_pdf_with_config};
+
+    /// Builds a PDF where each page is a list of `(x, y, text)` placements.
+    fn build_pdf(pages: &[Vec<(i64, i64, &str)>]) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+
+        let mut kids = Vec::new();
+        for placements in pages {
+            let mut operations = Vec::new();
+            for (x, y, text) in placements {
+                operations.push(Operation::new("BT", vec![]));
+                operations.push(Operation::new("Tf", vec!["F1".into(), 12.into()]));
+                operations.push(Operation::new("Td", vec![(*x).into(), (*y).into()]));
+                operations.push(Operation::new("Tj", vec![Object::string_literal(*text)]));
+                operations.push(Operation::new("ET", vec![]));
+            }
+            let content = Content { operations };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            });
+            kids.push(page_id.into());
+        }
+
+        let count = kids.len() as i64;
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_one_document_per_page() {
+        let pdf = build_pdf(&[
+            vec![(72, 750, "Quarterly report")],
+            vec![(72, 750, "Second page")],
+        ]);
+
+        let result = parse_pdf(&pdf).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["page_number"], 1);
+        assert_eq!(result[0]["text"], "Quarterly report");
+        assert_eq!(result[1]["page_number"], 2);
+        assert_eq!(result[1]["text"], "Second page");
+    }
+
+    #[test]
+    fn test_table_mode_emits_rows() {
+        let pdf = build_pdf(&[vec![
+            (72, 760, "Sales by region"),
+            (72, 730, "Region"), (250, 730, "Units"), (400, 730, "Revenue"),
+            (72, 715, "North"), (250, 715, "120"), (400, 715, "1,500.00"),
+            (72, 700, "South West"), (250, 700, "80"), (400, 700, "990.50"),
+            (72, 670, "Totals are unaudited."),
+        ]]);
+
+        let result = parse_pdf_with_config(&pdf, Some(&json!({"mode": "tables"}))).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], json!({"_pdf": {"page": 1, "table": 1, "row": 1}, "Region": "North", "Units": "120", "Revenue": "1,500.00"}));
+        assert_eq!(result[1]["Region"], "South West");
+    }
+
+    #[test]
+    fn test_table_columns_do_not_overwrite_provenance() {
+        let pdf = build_pdf(&[vec![
+            (72, 730, "page"), (250, 730, "row_number"),
+            (72, 715, "cover"), (250, 715, "7"),
+            (72, 700, "index"), (250, 700, "9"),
+        ]]);
+
+        let result = parse_pdf_with_config(&pdf, Some(&json!({"mode": "tables"}))).unwrap();
+
+        assert_eq!(result[1]["page"], "index");
+        assert_eq!(result[1]["row_number"], "9");
+        assert_eq!(result[1]["_pdf"], json!({"page": 1, "table": 1, "row": 2}));
+    }
+
+    #[test]
+    fn test_invalid_pdf_is_a_parse_error() {
+        assert!(parse_pdf(b"%PDF-1.4 not really").is_err());
+    }
+
+    #[test]
+    fn test_unknown_mode_is_rejected() {
+        let pdf = build_pdf(&[vec![(72, 750, "x")]]);
+        assert!(parse_pdf_with_config(&pdf, Some(&json!({"mode": "images"}))).is_err());
+    }
+}
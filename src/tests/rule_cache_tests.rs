@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use crate::domain::{error::IngestionError, models::IngestionConfigRule};
+    use crate::infrastructure::rule_cache::RuleCache;
+
+    fn rules() -> Vec<IngestionConfigRule> {
+        vec![
+            IngestionConfigRule::new(".*\\.csv$", "csv_data"),
+            IngestionConfigRule::new("reports/.*\\.xlsx$", "excel_reports"),
+            IngestionConfigRule::new("^data/.*", "data"),
+        ]
+    }
+
+    async fn load(loads: &AtomicUsize) -> Result<Vec<IngestionConfigRule>, IngestionError> {
+        loads.fetch_add(1, Ordering::SeqCst);
+        Ok(rules())
+    }
+
+    #[tokio::test]
+    async fn test_rules_are_loaded_once_within_ttl() {
+        let cache = RuleCache::new(Duration::from_secs(60));
+        let loads = AtomicUsize::new(0);
+
+        for _ in 0..3 {
+            let rules = cache.get_or_load(|| load(&loads)).await.unwrap();
+            assert_eq!(rules.rules().len(), 3);
+        }
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_or_invalidated_rules_are_reloaded() {
+        let loads = AtomicUsize::new(0);
+
+        let expiring = RuleCache::new(Duration::ZERO);
+        expiring.get_or_load(|| load(&loads)).await.unwrap();
+        expiring.get_or_load(|| load(&loads)).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        let cache = RuleCache::new(Duration::from_secs(60));
+        cache.get_or_load(|| load(&loads)).await.unwrap();
+        cache.invalidate().await;
+        cache.get_or_load(|| load(&loads)).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_is_not_cached() {
+        let cache = RuleCache::default();
+
+        let failed = cache.get_or_load(|| async { Err(IngestionError::Database("unavailable".to_string())) }).await;
+        assert!(failed.is_err());
+
+        let loads = AtomicUsize::new(0);
+        cache.get_or_load(|| load(&loads)).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::domain::{error::IngestionError, models::IngestionConfigRule};
+    use crate::infrastructure::rule_validator::RuleValidator;
+
+    fn error_message(rule: &IngestionConfigRule) -> String {
+        match RuleValidator::default().validate(rule) {
+            Err(IngestionError::Config(message)) => message,
+            other => panic!("expected a config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_valid_rules_pass() {
+        let validator = RuleValidator::default();
+
+        assert!(validator.validate(&IngestionConfigRule::new(".*\\.csv$", "orders")).is_ok());
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"headers": ["a", "b"], "delimiter": ";", "batch_size": 500})),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        assert!(validator.validate(&rule).is_ok());
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"line_pattern": "^(?P<level>\\w+) (?P<message>.*)$"})),
+            file_type: Some("log".to_string()),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        assert!(validator.validate(&rule).is_ok());
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"encoding": null})),
+            file_type: Some("csv".to_string()),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        assert!(validator.validate(&rule).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_key_suggests_the_closest_option() {
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"header": ["a", "b"]})),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        let message = error_message(&rule);
+
+        assert_eq!(message, "Rule '.*\\.csv$' (target 'orders'): unknown parser_config key 'header', did you mean 'headers'?");
+    }
+
+    #[test]
+    fn test_keys_are_checked_against_the_forced_parser() {
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"sheet_name": "Orders"})),
+            file_type: Some("CSV".to_string()),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        let message = error_message(&rule);
+
+        assert!(message.ends_with("unknown parser_config key 'sheet_name'"), "{}", message);
+    }
+
+    #[test]
+    fn test_keys_are_checked_against_the_parsers_for_the_pattern_extension() {
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"sheet_name": "Orders"})),
+            ..IngestionConfigRule::new(".*\\.csv\\.gz$", "orders")
+        };
+        assert!(error_message(&rule).ends_with("unknown parser_config key 'sheet_name'"));
+
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"sheet_name": "Orders"})),
+            ..IngestionConfigRule::new("(?i).*\\.(XLSX|ods)$", "orders")
+        };
+        assert!(RuleValidator::default().validate(&rule).is_ok());
+
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"sheet_name": "Orders"})),
+            ..IngestionConfigRule::new("^exports/.*", "orders")
+        };
+        assert!(RuleValidator::default().validate(&rule).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_type_is_reported() {
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"batch_size": "500"})),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        let message = error_message(&rule);
+        assert!(message.ends_with("parser_config.batch_size must be integer, got string"), "{}", message);
+
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"trim": 1})),
+            file_type: Some("csv".to_string()),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        let message = error_message(&rule);
+        assert!(message.ends_with("parser_config.trim must be boolean or string, got integer"), "{}", message);
+
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!(["headers"])),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        let message = error_message(&rule);
+        assert!(message.ends_with("parser_config must be an object, got array"), "{}", message);
+    }
+
+    #[test]
+    fn test_invalid_regexes_are_reported() {
+        let mut invalid_pattern = IngestionConfigRule::new(".*\\.csv$", "orders");
+        invalid_pattern.pattern = "data/(.*\\.csv".to_string();
+        assert!(error_message(&invalid_pattern).contains("invalid regex in pattern"));
+
+        let rule = IngestionConfigRule {
+            parser_config: Some(json!({"line_pattern": "(?P<level>\\w+"})), file_type: Some("log".to_string()),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        let message = error_message(&rule);
+        assert!(message.contains("invalid regex in parser_config.line_pattern"), "{}", message);
+    }
+
+    #[test]
+    fn test_unknown_file_type_is_reported() {
+        let rule = IngestionConfigRule {
+            file_type: Some("docx".to_string()),
+            ..IngestionConfigRule::new(".*\\.csv$", "orders")
+        };
+        let message = error_message(&rule);
+
+        assert!(message.ends_with("no parser is registered for file_type 'docx'"), "{}", message);
+    }
+
+    #[test]
+    fn test_invalid_matchers_are_reported() {
+        let mut bad_bucket = IngestionConfigRule::new(".*\\.csv$", "orders");
+        bad_bucket.matchers.bucket = Some("raw-(eu".to_string());
+        assert!(error_message(&bad_bucket).contains("invalid regex in matchers.bucket"));
+
+        let mut bad_range = IngestionConfigRule::new(".*\\.csv$", "orders");
+        bad_range.matchers.min_size = Some(10);
+        bad_range.matchers.max_size = Some(5);
+        assert!(error_message(&bad_range).ends_with("matchers.min_size 10 is greater than matchers.max_size 5"));
+    }
+
+    #[test]
+    fn test_invalid_versioning_is_reported() {
+        let mut zero = IngestionConfigRule::new(".*\\.csv$", "orders");
+        zero.version = 0;
+        assert!(error_message(&zero).ends_with("version must be at least 1"));
+
+        let mut inverted = IngestionConfigRule::new(".*\\.csv$", "orders");
+        inverted.valid_from = Some("2030-01-02T00:00:00Z".parse().unwrap());
+        inverted.valid_until = Some("2030-01-01T00:00:00Z".parse().unwrap());
+        assert!(error_message(&inverted).contains("is not before valid_until"));
+    }
+
+    #[test]
+    fn test_invalid_transform_is_reported() {
+        let mut invalid = IngestionConfigRule::new(".*\\.csv$", "orders");
+        invalid.transform = Some(json!([{"split": {"field": "tags"}}]));
+
+        let message = error_message(&invalid);
+
+        assert!(message.starts_with("Rule '.*\\.csv$' (target 'orders'): transform[0]: missing field `separator`"), "{}", message);
+    }
+
+    #[test]
+    fn test_invalid_schema_and_reject_ratio_are_reported() {
+        let mut invalid = IngestionConfigRule::new(".*\\.csv$", "orders");
+        invalid.schema = Some(json!({"type": 5}));
+        assert!(error_message(&invalid).contains("): invalid schema: "), "{}", error_message(&invalid));
+
+        let mut invalid = IngestionConfigRule::new(".*\\.csv$", "orders");
+        invalid.max_reject_ratio = Some(1.5);
+        assert!(error_message(&invalid).ends_with("max_reject_ratio must be between 0 and 1, got 1.5"));
+    }
+}
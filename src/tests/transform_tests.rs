@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use crate::domain::{error::IngestionError, transform::Transform};
+
+    fn transform(config: Value) -> Transform {
+        Transform::from_config(Some(&config)).expect("transform should compile")
+    }
+
+    fn config_error(config: Value) -> String {
+        match Transform::from_config(Some(&config)) {
+            Err(IngestionError::Config(message)) => message,
+            other => panic!("expected a config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_transform_passes_documents_through() {
+        let transform = Transform::from_config(None).unwrap();
+
+        assert!(transform.is_empty());
+        assert_eq!(transform.apply(json!({"a": 1})).unwrap(), json!({"a": 1}));
+        assert_eq!(transform.apply(json!([1, 2])).unwrap(), json!([1, 2]));
+    }
+
+    #[test]
+    fn test_rename_drop_and_set() {
+        let transform = transform(json!([
+            {"rename": {"cust_nm": "customer_name", "a": "b", "b": "a"}},
+            {"drop": ["internal_id", "not_there"]},
+            {"set": {"source": "crm", "version": 2}}
+        ]));
+
+        let result = transform.apply(json!({"cust_nm": "Ann", "a": 1, "b": 2, "internal_id": 7})).unwrap();
+
+        assert_eq!(result, json!({"customer_name": "Ann", "a": 2, "b": 1, "source": "crm", "version": 2}));
+    }
+
+    #[test]
+    fn test_template_renders_fields_and_escaped_braces() {
+        let transform = transform(json!([
+            {"template": {"full_name": "{first} {last}", "label": "{{{id}}} {missing}"}}
+        ]));
+
+        let result = transform.apply(json!({"first": "Ann", "last": "Lee", "id": 42, "missing": null})).unwrap();
+
+        assert_eq!(result["full_name"], "Ann Lee");
+        assert_eq!(result["label"], "{42} ");
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected_up_front() {
+        assert_eq!(config_error(json!([{"template": {"x": "{first"}}])), "transform[0].template.x: unclosed placeholder in '{first'");
+        assert_eq!(config_error(json!([{"template": {"x": "a}"}}])), "transform[0].template.x: unmatched '}' in 'a}'");
+        assert_eq!(config_error(json!([{"template": {"x": "{}"}}])), "transform[0].template.x: empty placeholder in '{}'");
+    }
+
+    #[test]
+    fn test_split_and_concat() {
+        let transform = transform(json!([
+            {"split": {"field": "tags", "separator": ",", "trim": true}},
+            {"split": {"field": "code", "separator": "-", "into": "code_parts"}},
+            {"concat": {"fields": ["city", "zip", "none"], "separator": " ", "into": "location"}}
+        ]));
+
+        let result = transform.apply(json!({"tags": "a, b ,c", "code": "X-1", "city": "Paris", "zip": 75001})).unwrap();
+
+        assert_eq!(result["tags"], json!(["a", "b", "c"]));
+        assert_eq!(result["code"], "X-1");
+        assert_eq!(result["code_parts"], json!(["X", "1"]));
+        assert_eq!(result["location"], "Paris 75001");
+    }
+
+    #[test]
+    fn test_split_of_a_non_string_fails() {
+        let transform = transform(json!([{"split": {"field": "n", "separator": ","}}]));
+
+        assert_eq!(transform.apply(json!({"n": 5})).unwrap_err(), "cannot split field 'n': not a string");
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let transform = transform(json!([
+            {"parse_date": {"field": "created", "format": "%d/%m/%Y"}},
+            {"parse_date": {"field": "updated", "into": "updated_at"}}
+        ]));
+
+        let result = transform.apply(json!({"created": "31/12/2024", "updated": "2024-01-02T03:04:05Z"})).unwrap();
+
+        assert_eq!(result["created"], "2024-12-31");
+        assert_eq!(result["updated_at"], "2024-01-02T03:04:05+00:00");
+
+        let error = transform.apply(json!({"created": "2024-12-31"})).unwrap_err();
+        assert_eq!(error, "cannot parse field 'created' value '2024-12-31' as a date with format '%d/%m/%Y'");
+    }
+
+    #[test]
+    fn test_nest_builds_sub_objects() {
+        let transform = transform(json!([{"nest": {}}]));
+
+        let result = transform.apply(json!({
+            "name": "Ann",
+            "address.city": "Paris",
+            "address.geo.lat": 48.8,
+            "address.geo.lon": 2.3
+        })).unwrap();
+
+        assert_eq!(result, json!({"name": "Ann", "address": {"city": "Paris", "geo": {"lat": 48.8, "lon": 2.3}}}));
+    }
+
+    #[test]
+    fn test_nest_reports_conflicts() {
+        let transform = transform(json!([{"nest": {"separator": "__"}}]));
+
+        let error = transform.apply(json!({"address": "Paris", "address__city": "Paris"})).unwrap_err();
+
+        assert!(error.contains("address"), "{}", error);
+    }
+
+    #[test]
+    fn test_steps_run_in_order() {
+        let transform = transform(json!([
+            {"rename": {"city": "address.city"}},
+            {"template": {"address.label": "{address.city} ({country})"}},
+            {"drop": ["country"]},
+            {"nest": {}}
+        ]));
+
+        let result = transform.apply(json!({"city": "Paris", "country": "FR"})).unwrap();
+
+        assert_eq!(result, json!({"address": {"city": "Paris", "label": "Paris (FR)"}}));
+    }
+
+    #[test]
+    fn test_invalid_steps_are_rejected() {
+        assert_eq!(config_error(json!({"rename": {}})), "transform must be an array of steps");
+        assert!(config_error(json!([{"rename": {}}, {"uppercase": ["a"]}])).starts_with("transform[1]: unknown variant `uppercase`"));
+        assert!(config_error(json!([{"split": {"field": "a", "separator": ",", "limit": 2}}])).contains("unknown field `limit`"));
+        assert_eq!(config_error(json!([{"split": {"field": "a", "separator": ""}}])), "transform[0].split: separator must not be empty");
+    }
+}
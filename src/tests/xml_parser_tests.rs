@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::infrastructure::parsers::xml_parser::{parse_xml, parse_xml_with_config};
+    use serde_json::json;
+
+    #[test]
+    fn test_xml_default_record_elements() {
+        let xml = br#"<data><record id="1"><name>John</name><age>25</age></record><record id="2"><name>Jane</name></record></data>"#;
+        let result = parse_xml(xml).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], json!({"@id": "1", "name": "John", "age": "25"}));
+        assert_eq!(result[1]["name"], "Jane");
+    }
+
+    #[test]
+    fn test_xml_record_path_with_nested_and_repeated_elements() {
+        let xml = br#"<?xml version="1.0"?>
+            <Orders>
+                <Meta><Order>ignored</Order></Meta>
+                <Order number="A1">
+                    <Customer><Name>John</Name><Address><City>Paris</City></Address></Customer>
+                    <Line sku="X">2</Line>
+                    <Line sku="Y">5</Line>
+                    <Note/>
+                </Order>
+            </Orders>"#;
+        let config = json!({"record_path": "/Orders/Order"});
+        let result = parse_xml_with_config(xml, Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0]["@number"], "A1");
+        assert_eq!(result[0]["Customer"]["Address"]["City"], "Paris");
+        assert_eq!(result[0]["Line"], json!([{"@sku": "X", "#text": "2"}, {"@sku": "Y", "#text": "5"}]));
+        assert_eq!(result[0]["Note"], "");
+    }
+
+    #[test]
+    fn test_xml_suffix_path_matches_any_depth() {
+        let xml = b"<feed><batch><item><v>1</v></item></batch><batch><item><v>2</v></item></batch></feed>";
+        let config = json!({"record_path": "//batch/item"});
+        let result = parse_xml_with_config(xml, Some(&config)).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[1]["v"], "2");
+    }
+
+    #[test]
+    fn test_xml_namespaces() {
+        let xml = br#"<ns:Feed xmlns:ns="urn:vendor"><ns:Item ns:code="7"><ns:Price>9.5</ns:Price></ns:Item></ns:Feed>"#;
+        let config = json!({"record_path": "/Feed/Item"});
+        let result = parse_xml_with_config(xml, Some(&config)).unwrap();
+        assert_eq!(result[0], json!({"@code": "7", "Price": "9.5"}));
+
+        let config = json!({"record_path": "/ns:Feed/ns:Item", "namespaces": "keep", "include_attributes": false});
+        let result = parse_xml_with_config(xml, Some(&config)).unwrap();
+        assert_eq!(result[0], json!({"ns:Price": "9.5"}));
+    }
+
+    #[test]
+    fn test_xml_attribute_and_child_with_the_same_name_both_survive() {
+        let xml = br#"<data><record id="1"><id>A-1</id></record></data>"#;
+
+        let result = parse_xml(xml).unwrap();
+        assert_eq!(result[0], json!({"@id": "1", "id": "A-1"}));
+
+        let config = json!({"attribute_prefix": "attr_"});
+        let result = parse_xml_with_config(xml, Some(&config)).unwrap();
+        assert_eq!(result[0], json!({"attr_id": "1", "id": "A-1"}));
+    }
+
+    #[test]
+    fn test_xml_no_matching_records() {
+        let config = json!({"record_path": "/Orders/Order"});
+        let err = parse_xml_with_config(b"<Other><Order/></Other>", Some(&config)).unwrap_err();
+
+        assert!(err.to_string().contains("/Orders/Order"));
+    }
+}